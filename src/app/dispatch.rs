@@ -0,0 +1,231 @@
+use serde_json::{Map, Value};
+
+use crate::github::Workflow;
+use crate::workflow::DispatchInput;
+
+/// Where the dispatch popup is: picking a workflow, then a ref, then filling
+/// in the workflow's inputs.
+#[derive(Debug, Clone)]
+pub enum DispatchStep {
+    Workflows {
+        workflows: Vec<Workflow>,
+        selected: usize,
+    },
+    Refs {
+        /// Branches and tags, the default branch first.
+        refs: Vec<String>,
+        /// Typed text narrowing `refs`; used as the ref itself when nothing
+        /// matches.
+        filter: String,
+        /// Position among the refs matching `filter`.
+        selected: usize,
+    },
+    Inputs {
+        inputs: Vec<DispatchInput>,
+        selected: usize,
+    },
+}
+
+/// State of the popup that starts a `workflow_dispatch` run.
+#[derive(Debug, Clone)]
+pub struct Dispatch {
+    pub step: DispatchStep,
+    pub workflow: Option<Workflow>,
+    pub git_ref: Option<String>,
+    /// What is being fetched or sent, shown with a spinner.
+    pub loading: Option<&'static str>,
+    pub error: Option<String>,
+}
+
+impl Dispatch {
+    pub fn new() -> Self {
+        Self {
+            step: DispatchStep::Workflows {
+                workflows: Vec::new(),
+                selected: 0,
+            },
+            workflow: None,
+            git_ref: None,
+            loading: Some("Loading workflows"),
+            error: None,
+        }
+    }
+
+    /// Disabled workflows can't be dispatched, so only active ones are
+    /// offered.
+    pub fn set_workflows(&mut self, workflows: Vec<Workflow>) {
+        self.loading = None;
+        let workflows: Vec<Workflow> = workflows
+            .into_iter()
+            .filter(Workflow::is_active)
+            .collect();
+        if workflows.is_empty() {
+            self.error = Some("No active workflows in this repository".to_string());
+        }
+        self.step = DispatchStep::Workflows {
+            workflows,
+            selected: 0,
+        };
+    }
+
+    pub fn set_refs(&mut self, mut refs: Vec<String>, default_branch: &str) {
+        self.loading = None;
+        if let Some(i) = refs.iter().position(|name| name == default_branch) {
+            let default = refs.remove(i);
+            refs.insert(0, default);
+        }
+        self.step = DispatchStep::Refs {
+            refs,
+            filter: String::new(),
+            selected: 0,
+        };
+    }
+
+    pub fn set_inputs(&mut self, inputs: Vec<DispatchInput>) {
+        self.loading = None;
+        self.step = DispatchStep::Inputs {
+            inputs,
+            selected: 0,
+        };
+    }
+
+    /// Refs matching the typed filter, ignoring case.
+    pub fn matching_refs(&self) -> Vec<&str> {
+        let DispatchStep::Refs { refs, filter, .. } = &self.step else {
+            return Vec::new();
+        };
+        let filter = filter.to_lowercase();
+        refs.iter()
+            .map(String::as_str)
+            .filter(|name| name.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let len = match &self.step {
+            DispatchStep::Workflows { workflows, .. } => workflows.len(),
+            DispatchStep::Refs { .. } => self.matching_refs().len(),
+            DispatchStep::Inputs { inputs, .. } => inputs.len(),
+        };
+        let (DispatchStep::Workflows { selected, .. }
+        | DispatchStep::Refs { selected, .. }
+        | DispatchStep::Inputs { selected, .. }) = &mut self.step;
+        *selected = selected
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+    }
+
+    /// The workflow under the cursor, remembered as the one to dispatch.
+    pub fn choose_workflow(&mut self) -> Option<Workflow> {
+        let DispatchStep::Workflows { workflows, selected } = &self.step else {
+            return None;
+        };
+        let workflow = workflows.get(*selected)?.clone();
+        self.workflow = Some(workflow.clone());
+        Some(workflow)
+    }
+
+    /// The ref under the cursor, or the typed one when nothing matches.
+    pub fn choose_ref(&mut self) -> Option<String> {
+        let DispatchStep::Refs {
+            filter, selected, ..
+        } = &self.step
+        else {
+            return None;
+        };
+        let git_ref = match self.matching_refs().get(*selected) {
+            Some(name) => name.to_string(),
+            None if !filter.trim().is_empty() => filter.trim().to_string(),
+            None => return None,
+        };
+        self.git_ref = Some(git_ref.clone());
+        Some(git_ref)
+    }
+
+    /// Type `c` into the ref filter or the selected text input.
+    pub fn type_char(&mut self, c: char) {
+        match &mut self.step {
+            DispatchStep::Refs {
+                filter, selected, ..
+            } => {
+                filter.push(c);
+                *selected = 0;
+            }
+            DispatchStep::Inputs { inputs, selected } => {
+                if let Some(input) = inputs.get_mut(*selected).filter(|input| input.is_text()) {
+                    input.value.push(c);
+                    input.error = None;
+                }
+            }
+            DispatchStep::Workflows { .. } => {}
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        match &mut self.step {
+            DispatchStep::Refs {
+                filter, selected, ..
+            } => {
+                filter.pop();
+                *selected = 0;
+            }
+            DispatchStep::Inputs { inputs, selected } => {
+                if let Some(input) = inputs.get_mut(*selected).filter(|input| input.is_text()) {
+                    input.value.pop();
+                    input.error = None;
+                }
+            }
+            DispatchStep::Workflows { .. } => {}
+        }
+    }
+
+    /// Step the selected boolean, choice or environment input.
+    pub fn cycle_input(&mut self, forward: bool) {
+        if let DispatchStep::Inputs { inputs, selected } = &mut self.step
+            && let Some(input) = inputs.get_mut(*selected)
+        {
+            input.cycle(forward);
+        }
+    }
+
+    /// Whether the selected input is typed into, so Space is text rather
+    /// than a toggle.
+    pub fn editing_text(&self) -> bool {
+        match &self.step {
+            DispatchStep::Refs { .. } => true,
+            DispatchStep::Inputs { inputs, selected } => {
+                inputs.get(*selected).is_some_and(DispatchInput::is_text)
+            }
+            DispatchStep::Workflows { .. } => false,
+        }
+    }
+
+    /// The inputs as sent to GitHub, or `None` with the offending inputs
+    /// marked when any is invalid. Empty optional inputs are left out so
+    /// the workflow's defaults apply.
+    pub fn input_values(&mut self) -> Option<Map<String, Value>> {
+        let DispatchStep::Inputs { inputs, selected } = &mut self.step else {
+            return None;
+        };
+        let mut values = Map::new();
+        let mut valid = true;
+        for input in inputs.iter_mut() {
+            match input.validate() {
+                Some(value) => {
+                    if !input.value.trim().is_empty() {
+                        values.insert(input.name.clone(), value);
+                    }
+                }
+                None => valid = false,
+            }
+        }
+        if !valid {
+            // Show the first problem
+            if let Some(i) = inputs.iter().position(|input| input.error.is_some()) {
+                *selected = i;
+            }
+            return None;
+        }
+        Some(values)
+    }
+}
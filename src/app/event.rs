@@ -0,0 +1,74 @@
+use crossterm::event::{self, Event as TerminalEvent};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::{Interval, MissedTickBehavior};
+
+use super::tasks::TaskResult;
+
+/// How often the UI ticks to animate spinners and countdowns.
+const TICK_RATE: Duration = Duration::from_millis(100);
+
+/// How long the input thread waits for a terminal event before checking
+/// whether the event loop is still listening.
+const INPUT_POLL: Duration = Duration::from_millis(250);
+
+/// Everything the main loop reacts to.
+#[derive(Debug)]
+pub enum Event {
+    Terminal(TerminalEvent),
+    Tick,
+    /// A background task finished; `id` tells results of cancelled tasks
+    /// apart from current ones.
+    Task { id: u64, result: Box<TaskResult> },
+}
+
+/// Multiplexes terminal input, the tick timer and results sent by background
+/// tasks into a single stream of [`Event`]s.
+pub struct EventLoop {
+    tx: UnboundedSender<Event>,
+    rx: UnboundedReceiver<Event>,
+    tick: Interval,
+}
+
+impl EventLoop {
+    /// Start reading terminal input on a dedicated thread, since crossterm's
+    /// reads block.
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let input_tx = tx.clone();
+        thread::spawn(move || {
+            while !input_tx.is_closed() {
+                match event::poll(INPUT_POLL) {
+                    Ok(true) => match event::read() {
+                        Ok(event) => {
+                            let _ = input_tx.send(Event::Terminal(event));
+                        }
+                        Err(_) => break,
+                    },
+                    Ok(false) => {}
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let mut tick = tokio::time::interval(TICK_RATE);
+        tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        Self { tx, rx, tick }
+    }
+
+    /// Channel background tasks report their results on.
+    pub fn sender(&self) -> UnboundedSender<Event> {
+        self.tx.clone()
+    }
+
+    pub async fn next(&mut self) -> Event {
+        tokio::select! {
+            // `self.tx` keeps the channel open, so `recv` never yields `None`
+            Some(event) = self.rx.recv() => event,
+            _ = self.tick.tick() => Event::Tick,
+        }
+    }
+}
@@ -0,0 +1,619 @@
+use std::cell::Cell;
+use std::time::Instant;
+
+/// What a log line is, from the workflow command it starts with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineKind {
+    Plain,
+    /// `##[group]`: the first line of a foldable section.
+    GroupStart,
+    /// `##[endgroup]`: never shown.
+    GroupEnd,
+    Error,
+    Warning,
+    Notice,
+    Command,
+    Debug,
+}
+
+const COMMANDS: &[(&str, LineKind)] = &[
+    ("##[group]", LineKind::GroupStart),
+    ("##[endgroup]", LineKind::GroupEnd),
+    ("##[error]", LineKind::Error),
+    ("##[warning]", LineKind::Warning),
+    ("##[notice]", LineKind::Notice),
+    ("##[command]", LineKind::Command),
+    ("##[debug]", LineKind::Debug),
+];
+
+/// One line of a job log. The text is kept as downloaded, ANSI escapes
+/// included; only the lines on screen are ever styled.
+#[derive(Debug)]
+pub struct LogLine {
+    text: String,
+    /// Where the message starts, after the timestamp.
+    message_start: usize,
+    /// Where the message starts, after the timestamp and workflow command.
+    content_start: usize,
+    pub kind: LineKind,
+}
+
+impl LogLine {
+    fn parse(text: &str) -> Self {
+        // Lines start with an RFC 3339 timestamp and a space
+        let message_start = text
+            .split_once(' ')
+            .filter(|(timestamp, _)| {
+                timestamp.len() >= 20
+                    && timestamp.ends_with('Z')
+                    && timestamp.as_bytes()[4] == b'-'
+                    && timestamp.as_bytes()[10] == b'T'
+            })
+            .map_or(0, |(timestamp, _)| timestamp.len() + 1);
+
+        let message = &text[message_start..];
+        let (kind, command_len) = COMMANDS
+            .iter()
+            .find(|(prefix, _)| message.starts_with(prefix))
+            .map_or((LineKind::Plain, 0), |(prefix, kind)| (*kind, prefix.len()));
+
+        Self {
+            text: text.to_string(),
+            message_start,
+            content_start: message_start + command_len,
+            kind,
+        }
+    }
+
+    pub fn timestamp(&self) -> Option<&str> {
+        (self.message_start > 0).then(|| self.text[..self.message_start].trim_end())
+    }
+
+    /// The line without its timestamp or workflow command.
+    pub fn content(&self) -> &str {
+        &self.text[self.content_start..]
+    }
+}
+
+#[derive(Debug)]
+struct Group {
+    /// Index of the `##[group]` line.
+    start: usize,
+    /// Index of the `##[endgroup]` line, or the last line so far when
+    /// missing.
+    end: usize,
+    collapsed: bool,
+}
+
+/// Complete lines of a job log, parsed off the UI thread, and the number of
+/// bytes of the log they took up.
+#[derive(Debug)]
+pub struct LogChunk {
+    lines: Vec<LogLine>,
+    pub len: usize,
+}
+
+impl LogChunk {
+    /// Parse the lines of `bytes`. A last line without its newline is left
+    /// for the next chunk unless the log is `complete`, since the runner may
+    /// still be writing it. Even then, a character cut off by the end of the
+    /// download is left for the next request.
+    pub fn parse(bytes: &[u8], complete: bool) -> Self {
+        let len = if complete {
+            complete_utf8_len(bytes)
+        } else {
+            bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1)
+        };
+        let text = String::from_utf8_lossy(&bytes[..len]);
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        let lines = text
+            .lines()
+            .map(|line| LogLine::parse(line.strip_suffix('\r').unwrap_or(line)))
+            .collect();
+        Self { lines, len }
+    }
+}
+
+/// The length of `bytes` without a multi-byte character cut off at the end.
+/// Invalid UTF-8 elsewhere is kept, to be shown replaced.
+fn complete_utf8_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        // No error length means the input ends inside a character
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => bytes.len(),
+    }
+}
+
+/// A downloaded job log split into lines and foldable groups.
+#[derive(Debug, Default)]
+pub struct Log {
+    lines: Vec<LogLine>,
+    groups: Vec<Group>,
+    /// Group each line belongs to, by index into `groups`.
+    line_groups: Vec<Option<usize>>,
+    /// The group without an `##[endgroup]` yet, which later lines join.
+    open_group: Option<usize>,
+}
+
+impl Log {
+    fn extend(&mut self, lines: Vec<LogLine>) {
+        self.lines.reserve(lines.len());
+        self.line_groups.reserve(lines.len());
+        for line in lines {
+            let i = self.lines.len();
+            let group = match line.kind {
+                // Groups don't nest; a new group closes the one before
+                LineKind::GroupStart => {
+                    self.open_group = Some(self.groups.len());
+                    self.groups.push(Group {
+                        start: i,
+                        end: i,
+                        collapsed: true,
+                    });
+                    self.open_group
+                }
+                LineKind::GroupEnd => self.open_group.take(),
+                _ => self.open_group,
+            };
+            if let Some(group) = group {
+                self.groups[group].end = i;
+            }
+            self.line_groups.push(group);
+            self.lines.push(line);
+        }
+    }
+}
+
+/// Where the log view stands with its log.
+#[derive(Debug)]
+pub enum LogState {
+    Loading,
+    Failed(String),
+    Loaded(Log),
+}
+
+/// State of the full-screen log viewer for one job.
+#[derive(Debug)]
+pub struct LogView {
+    pub job_id: u64,
+    pub job_name: String,
+    pub state: LogState,
+    /// Whether the job was still running at the last fetch, so its log is
+    /// polled for more output.
+    pub live: bool,
+    /// Bytes of the log received so far; the next poll asks for the rest.
+    pub offset: usize,
+    /// When a live log is next polled.
+    pub next_poll: Option<Instant>,
+    /// Keep the cursor on the last line as output arrives. Off once the
+    /// user moves away from the bottom.
+    pub following: bool,
+    /// Indices into the log's lines that are shown, with collapsed groups
+    /// folded into their first line.
+    visible: Vec<usize>,
+    /// Position in `visible` of the line under the cursor.
+    pub cursor: usize,
+    /// First row drawn; adjusted while rendering to keep the cursor on
+    /// screen.
+    pub scroll: Cell<usize>,
+    /// Rows drawn by the last render, for paging.
+    pub height: Cell<usize>,
+    pub show_timestamps: bool,
+    /// Query of the last search, for `n` and `N`.
+    pub query: Option<String>,
+    /// Search being typed after `/`.
+    pub input: Option<String>,
+    /// Feedback such as "No matches", cleared on the next key.
+    pub message: Option<String>,
+}
+
+impl LogView {
+    pub fn new(job_id: u64, job_name: String, live: bool) -> Self {
+        Self {
+            job_id,
+            job_name,
+            state: LogState::Loading,
+            live,
+            offset: 0,
+            next_poll: None,
+            following: live,
+            visible: Vec::new(),
+            cursor: 0,
+            scroll: Cell::new(0),
+            height: Cell::new(0),
+            show_timestamps: false,
+            query: None,
+            input: None,
+            message: None,
+        }
+    }
+
+    /// Add newly downloaded lines to the log.
+    pub fn append(&mut self, chunk: LogChunk) {
+        self.offset += chunk.len;
+        let first = !matches!(self.state, LogState::Loaded(_));
+        if first {
+            self.state = LogState::Loaded(Log::default());
+        }
+        let line = self.cursor_line().unwrap_or(0);
+        let LogState::Loaded(log) = &mut self.state else {
+            return;
+        };
+        log.extend(chunk.lines);
+        // Output of the step still running is what a tail is watched for
+        if self.live
+            && let Some(group) = log.open_group
+        {
+            log.groups[group].collapsed = false;
+        }
+        self.refold(line);
+
+        if self.following {
+            self.jump_to_end();
+        } else if first {
+            // Failed jobs are opened to find out why, so start at the first
+            // error
+            self.jump_to(true, |line| line.kind == LineKind::Error);
+        }
+    }
+
+    /// Follow new output of a live log when the cursor is on the last line.
+    pub fn update_following(&mut self) {
+        self.following = self.live && self.cursor + 1 >= self.visible.len();
+    }
+
+    fn log(&self) -> Option<&Log> {
+        match &self.state {
+            LogState::Loaded(log) => Some(log),
+            _ => None,
+        }
+    }
+
+    pub fn visible_len(&self) -> usize {
+        self.visible.len()
+    }
+
+    /// Lines in rows `rows` with their line index and, for the first line of
+    /// a group, whether the group is collapsed.
+    pub fn rows(
+        &self,
+        rows: std::ops::Range<usize>,
+    ) -> impl Iterator<Item = (usize, &LogLine, Option<bool>)> {
+        let log = self.log();
+        let end = rows.end.min(self.visible.len());
+        let start = rows.start.min(end);
+        self.visible[start..end].iter().filter_map(move |&i| {
+            let log = log?;
+            let line = &log.lines[i];
+            let folded = (line.kind == LineKind::GroupStart)
+                .then(|| log.line_groups[i].map(|group| log.groups[group].collapsed))
+                .flatten();
+            Some((i, line, folded))
+        })
+    }
+
+    /// Total number of lines in the log.
+    pub fn line_count(&self) -> usize {
+        self.log().map_or(0, |log| log.lines.len())
+    }
+
+    /// Line index under the cursor.
+    pub fn cursor_line(&self) -> Option<usize> {
+        self.visible.get(self.cursor).copied()
+    }
+
+    fn rebuild_visible(&mut self) {
+        let Some(log) = self.log() else {
+            self.visible.clear();
+            return;
+        };
+        let mut visible = Vec::with_capacity(log.lines.len());
+        let mut i = 0;
+        while i < log.lines.len() {
+            let line = &log.lines[i];
+            if line.kind == LineKind::GroupStart
+                && let Some(group) = log.line_groups[i].map(|group| &log.groups[group])
+                && group.collapsed
+            {
+                visible.push(i);
+                i = group.end + 1;
+                continue;
+            }
+            if line.kind != LineKind::GroupEnd {
+                visible.push(i);
+            }
+            i += 1;
+        }
+        self.visible = visible;
+    }
+
+    /// Rebuild the shown lines after folding, keeping the cursor on `line`
+    /// or the closest line shown before it.
+    fn refold(&mut self, line: usize) {
+        self.rebuild_visible();
+        self.cursor = self
+            .visible
+            .partition_point(|&shown| shown <= line)
+            .saturating_sub(1);
+    }
+
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.visible.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn page(&mut self, direction: isize) {
+        let height = self.height.get().max(1) as isize;
+        self.move_cursor(direction * height);
+    }
+
+    pub fn jump_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn jump_to_end(&mut self) {
+        self.cursor = self.visible.len().saturating_sub(1);
+    }
+
+    /// Fold or unfold the group under the cursor. Inside an unfolded group,
+    /// folds that group.
+    pub fn toggle_fold(&mut self) {
+        let Some(line) = self.cursor_line() else {
+            return;
+        };
+        let LogState::Loaded(log) = &mut self.state else {
+            return;
+        };
+        let Some(group) = log.line_groups[line] else {
+            return;
+        };
+        let group = &mut log.groups[group];
+        group.collapsed = !group.collapsed;
+        let start = group.start;
+        self.refold(start);
+    }
+
+    /// Fold every group when any is unfolded, otherwise unfold them all.
+    pub fn toggle_all_folds(&mut self) {
+        let Some(line) = self.cursor_line() else {
+            return;
+        };
+        let LogState::Loaded(log) = &mut self.state else {
+            return;
+        };
+        let collapse = log.groups.iter().any(|group| !group.collapsed);
+        for group in &mut log.groups {
+            group.collapsed = collapse;
+        }
+        self.refold(line);
+    }
+
+    /// Move to the next (or previous) line matching `predicate`, wrapping
+    /// around and unfolding its group. Returns whether one was found.
+    fn jump_to(&mut self, forward: bool, predicate: impl Fn(&LogLine) -> bool) -> bool {
+        let current = self.cursor_line();
+        let LogState::Loaded(log) = &mut self.state else {
+            return false;
+        };
+        let count = log.lines.len();
+        if count == 0 {
+            return false;
+        }
+
+        // Start just past the cursor so repeated jumps advance
+        let start = match (current, forward) {
+            (Some(line), true) => line + 1,
+            (Some(line), false) => line + count - 1,
+            (None, _) => 0,
+        };
+        let found = (0..count)
+            .map(|offset| {
+                if forward {
+                    (start + offset) % count
+                } else {
+                    (start + count - offset) % count
+                }
+            })
+            .find(|&i| log.lines[i].kind != LineKind::GroupEnd && predicate(&log.lines[i]));
+
+        let Some(line) = found else {
+            return false;
+        };
+        if let Some(group) = log.line_groups[line] {
+            log.groups[group].collapsed = false;
+        }
+        self.refold(line);
+        true
+    }
+
+    pub fn next_error(&mut self, forward: bool) {
+        self.message = None;
+        if !self.jump_to(forward, |line| line.kind == LineKind::Error) {
+            self.message = Some("No errors".to_string());
+        }
+    }
+
+    /// Search for `query`, ignoring case, from the cursor.
+    pub fn search(&mut self, query: String) {
+        self.query = (!query.is_empty()).then_some(query);
+        self.next_match(true);
+    }
+
+    pub fn next_match(&mut self, forward: bool) {
+        self.message = None;
+        let Some(query) = self.query.clone() else {
+            return;
+        };
+        let needle = query.to_lowercase();
+        if !self.jump_to(forward, |line| {
+            strip_ansi(line.content()).to_lowercase().contains(&needle)
+        }) {
+            self.message = Some(format!("No matches for '{}'", query));
+        }
+    }
+}
+
+/// `text` without ANSI escape sequences.
+pub fn strip_ansi(text: &str) -> std::borrow::Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return text.into();
+    }
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+        // CSI sequences end with a byte in @..~; other escapes are one char
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    plain.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMESTAMP: &str = "2024-05-01T12:34:56.1234567Z";
+
+    fn view(source: &str, live: bool) -> LogView {
+        let mut view = LogView::new(1, "build".to_string(), live);
+        view.append(LogChunk::parse(source.as_bytes(), true));
+        view
+    }
+
+    fn contents(chunk: &LogChunk) -> Vec<&str> {
+        chunk.lines.iter().map(LogLine::content).collect()
+    }
+
+    fn shown(view: &LogView) -> Vec<String> {
+        view.rows(0..view.visible_len())
+            .map(|(_, line, _)| line.content().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn strips_timestamp_and_command() {
+        let line = LogLine::parse(&format!("{} ##[error]Process exited", TIMESTAMP));
+        assert_eq!(line.timestamp(), Some(TIMESTAMP));
+        assert_eq!(line.kind, LineKind::Error);
+        assert_eq!(line.content(), "Process exited");
+    }
+
+    #[test]
+    fn keeps_lines_without_timestamp() {
+        let line = LogLine::parse("Run actions/checkout@v4 ##[group]");
+        assert_eq!(line.timestamp(), None);
+        assert_eq!(line.kind, LineKind::Plain);
+        assert_eq!(line.content(), "Run actions/checkout@v4 ##[group]");
+
+        let line = LogLine::parse("##[warning]no timestamp");
+        assert_eq!(line.kind, LineKind::Warning);
+        assert_eq!(line.content(), "no timestamp");
+    }
+
+    #[test]
+    fn strips_bom_and_crlf() {
+        let source = "\u{feff}first\r\nsecond\r\n";
+        let chunk = LogChunk::parse(source.as_bytes(), true);
+        assert_eq!(contents(&chunk), ["first", "second"]);
+        assert_eq!(chunk.len, source.len());
+    }
+
+    #[test]
+    fn holds_back_partial_last_line() {
+        let chunk = LogChunk::parse(b"done\nhalf a li", false);
+        assert_eq!(contents(&chunk), ["done"]);
+        assert_eq!(chunk.len, 5);
+
+        let chunk = LogChunk::parse(b"done\nlast line", true);
+        assert_eq!(contents(&chunk), ["done", "last line"]);
+        assert_eq!(chunk.len, 14);
+
+        let chunk = LogChunk::parse(b"no newline yet", false);
+        assert!(chunk.lines.is_empty());
+        assert_eq!(chunk.len, 0);
+    }
+
+    #[test]
+    fn holds_back_cut_off_character() {
+        let bytes = "caf\u{e9}".as_bytes();
+        let chunk = LogChunk::parse(&bytes[..bytes.len() - 1], true);
+        assert_eq!(contents(&chunk), ["caf"]);
+        assert_eq!(chunk.len, 3);
+    }
+
+    #[test]
+    fn pairs_groups_and_folds_them() {
+        let view = view(
+            "##[group]Run tests\nrunning\n##[endgroup]\nafter\n##[group]Upload\nuploading\n",
+            false,
+        );
+        let LogState::Loaded(log) = &view.state else {
+            panic!("log not loaded");
+        };
+        assert_eq!((log.groups[0].start, log.groups[0].end), (0, 2));
+        // The unclosed group runs to the last line so far
+        assert_eq!((log.groups[1].start, log.groups[1].end), (4, 5));
+        assert_eq!(log.open_group, Some(1));
+        assert_eq!(log.line_groups, [Some(0), Some(0), Some(0), None, Some(1), Some(1)]);
+
+        assert_eq!(shown(&view), ["Run tests", "after", "Upload"]);
+    }
+
+    #[test]
+    fn unclosed_group_joins_later_chunks() {
+        let mut view = view("##[group]Build\ncompiling\n", true);
+        view.append(LogChunk::parse(b"linking\n##[endgroup]\nsummary\n", true));
+        let LogState::Loaded(log) = &view.state else {
+            panic!("log not loaded");
+        };
+        assert_eq!((log.groups[0].start, log.groups[0].end), (0, 3));
+        assert_eq!(log.open_group, None);
+        // A live log unfolds the step still running, and never shows endgroup
+        assert_eq!(shown(&view), ["Build", "compiling", "linking", "summary"]);
+    }
+
+    #[test]
+    fn new_group_closes_the_previous_one() {
+        let view = view("##[group]One\na\n##[group]Two\nb\n", false);
+        let LogState::Loaded(log) = &view.state else {
+            panic!("log not loaded");
+        };
+        assert_eq!((log.groups[0].start, log.groups[0].end), (0, 1));
+        assert_eq!((log.groups[1].start, log.groups[1].end), (2, 3));
+    }
+
+    #[test]
+    fn search_unfolds_match_and_wraps() {
+        let mut view = view(
+            "start\n##[group]Install\n\x1b[32mFetching\x1b[0m deps\n##[endgroup]\nfetching done\n",
+            false,
+        );
+        view.search("FETCHING DEPS".to_string());
+        assert_eq!(view.message, None);
+        assert_eq!(view.cursor_line(), Some(2));
+
+        view.search("fetching".to_string());
+        assert_eq!(view.cursor_line(), Some(4));
+        view.next_match(true);
+        assert_eq!(view.cursor_line(), Some(2));
+
+        view.search("missing".to_string());
+        assert_eq!(view.message.as_deref(), Some("No matches for 'missing'"));
+        assert_eq!(view.cursor_line(), Some(2));
+    }
+
+    #[test]
+    fn opens_failed_log_at_first_error() {
+        let view = view("setup\n##[group]Test\n##[error]boom\n##[endgroup]\n", false);
+        assert_eq!(view.cursor_line(), Some(2));
+    }
+}
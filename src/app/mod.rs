@@ -1,3 +1,13 @@
+pub mod dispatch;
+pub mod event;
+pub mod log_view;
 pub mod state;
+pub mod tasks;
+pub mod yaml_view;
 
-pub use state::{AppState, AuthState, Panel};
+pub use dispatch::Dispatch;
+pub use event::{Event, EventLoop};
+pub use log_view::{LogChunk, LogView};
+pub use state::{AppState, AuthState, Panel, PendingDispatch, Popup, RunAction, Session};
+pub use tasks::{TaskKind, TaskResult, Tasks};
+pub use yaml_view::{WorkflowSource, YamlView};
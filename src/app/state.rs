@@ -1,45 +1,267 @@
-use anyhow::Result;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::utils::error::AppError;
 
 #[derive(Debug, Clone)]
 pub enum AuthState {
     Unauthenticated,
     Authenticating,
-    Authenticated { username: String },
+    Authenticated { username: String, host: String },
     Error { message: String },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// The logged-in account's API client, built once per session and cloned
+/// into background tasks, rather than each task reading the credential
+/// store and asking the API who the user is.
+#[derive(Clone)]
+pub struct Session {
+    pub client: crate::github::GitHubClient,
+    pub token: crate::auth::TokenData,
+    /// When to try again after the token failed to refresh.
+    pub retry_refresh_at: Option<Instant>,
+}
+
+impl Session {
+    pub fn new(token: crate::auth::TokenData) -> Result<Self, AppError> {
+        Ok(Self {
+            client: crate::github::GitHubClient::from_token(&token)?,
+            token,
+            retry_refresh_at: None,
+        })
+    }
+}
+
+/// Modal popups drawn over the main panels; at most one is open at a time.
+#[derive(Debug, Clone)]
+pub enum Popup {
+    LoginMethod,
+    /// Shown when the browser couldn't be opened, while the callback server
+    /// keeps waiting for the login to be finished by hand.
+    BrowserLogin {
+        authorize_url: String,
+        browser_error: String,
+    },
+    DeviceCode {
+        authorization: crate::auth::DeviceAuthorization,
+        status: String,
+    },
+    TokenInput {
+        input: String,
+        error: Option<String>,
+    },
+    AccountPicker {
+        accounts: Vec<(crate::auth::Account, bool)>,
+        selected: usize,
+    },
+    /// Asks before re-running or cancelling a run.
+    ConfirmRunAction {
+        action: RunAction,
+        run_id: u64,
+        /// The run as shown in the popup, e.g. `CI #42`.
+        run_label: String,
+        /// Re-run with runner debug logging enabled.
+        debug_logging: bool,
+    },
+    /// Starts a `workflow_dispatch` run of the selected repository.
+    Dispatch(Box<super::Dispatch>),
+}
+
+/// A dispatched workflow whose run is waited for, to select it once a poll
+/// brings it in. GitHub doesn't say which run a dispatch created.
+#[derive(Debug, Clone)]
+pub struct PendingDispatch {
+    pub workflow_id: u64,
+    pub git_ref: String,
+    /// Runs up to this id existed before the dispatch; run ids only grow.
+    pub after_run_id: u64,
+    /// Give up after this, e.g. when the ref has no such workflow.
+    pub expires_at: Instant,
+}
+
+/// How long a dispatched run is waited for.
+const DISPATCH_WAIT: Duration = Duration::from_secs(120);
+
+impl PendingDispatch {
+    pub fn new(workflow_id: u64, git_ref: String, after_run_id: u64) -> Self {
+        Self {
+            workflow_id,
+            git_ref,
+            after_run_id,
+            expires_at: Instant::now() + DISPATCH_WAIT,
+        }
+    }
+}
+
+/// Something done to a workflow run from the Action Runs panel.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunAction {
+    Rerun,
+    RerunFailed,
+    RerunJob { job_id: u64, job_name: String },
+    Cancel,
+    ForceCancel,
+}
+
+impl RunAction {
+    /// What the action does, for the confirmation popup.
+    pub fn describe(&self, run_label: &str) -> String {
+        match self {
+            RunAction::Rerun => format!("Re-run all jobs of {}?", run_label),
+            RunAction::RerunFailed => format!("Re-run the failed jobs of {}?", run_label),
+            RunAction::RerunJob { job_name, .. } => {
+                format!("Re-run job '{}' of {}?", job_name, run_label)
+            }
+            RunAction::Cancel => format!("Cancel {}?", run_label),
+            RunAction::ForceCancel => format!(
+                "Force cancel {}? Jobs are stopped even if their conditions would keep them running.",
+                run_label
+            ),
+        }
+    }
+
+    pub fn is_rerun(&self) -> bool {
+        matches!(
+            self,
+            RunAction::Rerun | RunAction::RerunFailed | RunAction::RerunJob { .. }
+        )
+    }
+
+    /// Whether the action applies to `run`: re-runs to finished runs, and
+    /// re-running failed jobs only to runs that have some.
+    pub fn applies_to(&self, run: &crate::github::WorkflowRun) -> bool {
+        match self {
+            RunAction::Rerun | RunAction::RerunJob { .. } => run.is_completed(),
+            RunAction::RerunFailed => {
+                run.is_completed()
+                    && matches!(
+                        run.conclusion.as_deref(),
+                        Some("failure" | "cancelled" | "timed_out")
+                    )
+            }
+            RunAction::Cancel | RunAction::ForceCancel => !run.is_completed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Panel {
     Repositories,
+    Workflows,
     Actions,
     Details,
 }
 
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
 pub struct AppState {
     pub auth_state: AuthState,
+    /// Set while logged in.
+    pub session: Option<Session>,
     pub repos: Vec<crate::github::RepoWithActions>,
     pub selected_repo: Option<usize>,
     pub current_panel: Panel,
-    pub loading: bool,
-    pub actions: Vec<crate::github::WorkflowSummary>,
+    /// Whether the repository list is being fetched.
+    pub loading_repos: bool,
+    /// Whether a page of workflow runs is being fetched.
+    pub loading_actions: bool,
+    /// Workflows defined in the selected repository.
+    pub workflows: Vec<crate::github::Workflow>,
+    pub loading_workflows: bool,
+    /// Workflow whose runs are listed; all runs when `None`.
+    pub selected_workflow: Option<usize>,
+    /// Latest run seen of each workflow, by workflow id, for the Workflows
+    /// panel.
+    pub workflow_last_runs: HashMap<u64, crate::github::WorkflowRun>,
+    /// Advanced on every tick to animate spinners.
+    pub spinner_frame: usize,
+    pub actions: Vec<crate::github::WorkflowRun>,
     pub selected_action: Option<usize>,
+    /// Last page of workflow runs loaded into `actions` (0 when none are loaded).
+    pub actions_page: u32,
+    /// Total number of workflow runs GitHub reports for the selected repository.
+    pub actions_total: u64,
+    /// ETag of the latest first page of runs, sent with the next poll so an
+    /// unchanged page costs no rate limit.
+    pub runs_etag: Option<octocrab::etag::EntityTag>,
+    /// When the selected repository's runs are next polled.
+    pub next_refresh: Option<Instant>,
+    /// Jobs of the run `jobs_run_id`, shown in the Details panel.
+    pub jobs: Vec<crate::github::WorkflowJob>,
+    pub jobs_run_id: Option<u64>,
+    pub loading_jobs: bool,
+    pub selected_job: Option<usize>,
+    /// Selected step while the selected job's steps are open.
+    pub selected_step: Option<usize>,
+    /// Dispatched run to select once it appears.
+    pub pending_dispatch: Option<PendingDispatch>,
+    /// Log of a job, shown full screen over the panels while open.
+    pub log_view: Option<super::LogView>,
+    /// Workflow file, shown full screen over the panels while open.
+    pub yaml_view: Option<super::YamlView>,
+    pub popup: Option<Popup>,
+    /// Why the last background load failed, shown in the header until one
+    /// succeeds. Kept apart from `auth_state` so a network error doesn't log
+    /// the user out.
+    pub load_error: Option<String>,
+    /// Scopes and expiry of a personal access token, when logged in with one.
+    pub token_info: Option<crate::auth::TokenInfo>,
+    /// Instance new logins are made against.
+    pub login_host: crate::config::GitHubHost,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
             auth_state: AuthState::Unauthenticated,
+            session: None,
             repos: Vec::new(),
             selected_repo: None,
-            current_panel: Panel::Repositories,
-            loading: false,
+            current_panel: crate::config::get().ui.default_panel,
+            loading_repos: false,
+            loading_actions: false,
+            workflows: Vec::new(),
+            loading_workflows: false,
+            selected_workflow: None,
+            workflow_last_runs: HashMap::new(),
+            spinner_frame: 0,
             actions: Vec::new(),
             selected_action: None,
+            actions_page: 0,
+            actions_total: 0,
+            runs_etag: None,
+            next_refresh: None,
+            jobs: Vec::new(),
+            jobs_run_id: None,
+            loading_jobs: false,
+            selected_job: None,
+            selected_step: None,
+            pending_dispatch: None,
+            log_view: None,
+            yaml_view: None,
+            popup: None,
+            load_error: None,
+            token_info: None,
+            login_host: crate::config::get().login_host(),
         }
     }
 
-    pub fn set_loading(&mut self, loading: bool) {
-        self.loading = loading;
+    /// A client for a background task.
+    pub fn client(&self) -> Result<crate::github::GitHubClient, AppError> {
+        self.session
+            .as_ref()
+            .map(|session| session.client.clone())
+            .ok_or_else(|| AppError::AuthFailed("not logged in".to_string()))
+    }
+
+    pub fn tick(&mut self) {
+        self.spinner_frame = self.spinner_frame.wrapping_add(1);
+    }
+
+    /// The current frame of the busy spinner.
+    pub fn spinner(&self) -> char {
+        SPINNER_FRAMES[self.spinner_frame % SPINNER_FRAMES.len()]
     }
 
     pub fn move_selection(&mut self, direction: i32) {
@@ -50,8 +272,263 @@ impl AppState {
         }
     }
 
+    /// Move the workflow selection, where the row above the first workflow
+    /// stands for all workflows. Returns whether the selection changed.
+    pub fn move_workflow_selection(&mut self, direction: i32) -> bool {
+        let current = self.selected_workflow.map_or(0, |i| i + 1);
+        let row = (current as i32 + direction).clamp(0, self.workflows.len() as i32) as usize;
+        let selected = row.checked_sub(1);
+        let changed = selected != self.selected_workflow;
+        self.selected_workflow = selected;
+        changed
+    }
+
+    /// The workflow the runs are filtered by.
+    pub fn workflow_filter(&self) -> Option<&crate::github::Workflow> {
+        self.workflows.get(self.selected_workflow?)
+    }
+
+    pub fn clear_workflows(&mut self) {
+        self.workflows.clear();
+        self.loading_workflows = false;
+        self.selected_workflow = None;
+        self.workflow_last_runs.clear();
+    }
+
+    /// Show freshly fetched workflows, keeping the selected one if it still
+    /// exists.
+    pub fn set_workflows(&mut self, workflows: Vec<crate::github::Workflow>) {
+        let selected_id = self.workflow_filter().map(|workflow| workflow.id);
+        self.workflows = workflows;
+        self.selected_workflow =
+            selected_id.and_then(|id| self.workflows.iter().position(|w| w.id == id));
+    }
+
+    /// Remember the newest of `runs` of each workflow as its last run.
+    pub fn note_workflow_runs<'a>(
+        &mut self,
+        runs: impl IntoIterator<Item = &'a crate::github::WorkflowRun>,
+    ) {
+        for run in runs {
+            let newer = self
+                .workflow_last_runs
+                .get(&run.workflow_id)
+                .is_none_or(|last| run.id >= last.id);
+            if newer {
+                self.workflow_last_runs.insert(run.workflow_id, run.clone());
+            }
+        }
+    }
+
+    /// Move the run selection, clamping at both ends. Returns `true` when the
+    /// user tried to move past the last loaded run and more runs exist.
+    pub fn move_action_selection(&mut self, direction: i32) -> bool {
+        if self.actions.is_empty() {
+            return false;
+        }
+
+        let last = self.actions.len() - 1;
+        let current = self.selected_action.unwrap_or(0);
+        if direction > 0 && current == last && self.selected_action.is_some() {
+            return self.has_more_actions();
+        }
+
+        let new_pos = match self.selected_action {
+            None => 0,
+            Some(_) => (current as i32 + direction).clamp(0, last as i32) as usize,
+        };
+        self.selected_action = Some(new_pos);
+        false
+    }
+
+    pub fn has_more_actions(&self) -> bool {
+        (self.actions.len() as u64) < self.actions_total
+    }
+
+    pub fn clear_actions(&mut self) {
+        self.actions.clear();
+        self.loading_actions = false;
+        self.selected_action = None;
+        self.actions_page = 0;
+        self.actions_total = 0;
+        self.runs_etag = None;
+        self.next_refresh = None;
+        self.pending_dispatch = None;
+        self.clear_jobs();
+    }
+
+    /// Select the run a dispatch created, once it is among the loaded runs.
+    /// Returns whether it was found.
+    pub fn select_dispatched_run(&mut self) -> bool {
+        let Some(pending) = &self.pending_dispatch else {
+            return false;
+        };
+        if Instant::now() >= pending.expires_at {
+            self.pending_dispatch = None;
+            return false;
+        }
+        let branch = pending
+            .git_ref
+            .trim_start_matches("refs/heads/")
+            .trim_start_matches("refs/tags/");
+        let Some(index) = self.actions.iter().position(|run| {
+            run.id > pending.after_run_id
+                && run.workflow_id == pending.workflow_id
+                && run.event == "workflow_dispatch"
+                && run.head_branch.as_deref() == Some(branch)
+        }) else {
+            return false;
+        };
+        self.pending_dispatch = None;
+        self.selected_action = Some(index);
+        self.current_panel = Panel::Actions;
+        true
+    }
+
+    pub fn selected_run(&self) -> Option<&crate::github::WorkflowRun> {
+        self.actions.get(self.selected_action?)
+    }
+
+    /// Show the expected effect of `action` on run `run_id` before GitHub
+    /// confirms it; the next poll replaces it with the real state.
+    pub fn apply_run_action(&mut self, run_id: u64, action: &RunAction) {
+        let Some(run) = self.actions.iter_mut().find(|run| run.id == run_id) else {
+            return;
+        };
+        run.status = match action {
+            RunAction::Cancel | RunAction::ForceCancel => "cancelling",
+            _ => "queued",
+        }
+        .to_string();
+        if action.is_rerun() {
+            run.conclusion = None;
+        }
+
+        if let RunAction::RerunJob { job_id, .. } = action
+            && self.jobs_run_id == Some(run_id)
+            && let Some(job) = self.jobs.iter_mut().find(|job| job.id == *job_id)
+        {
+            job.status = "queued".to_string();
+            job.conclusion = None;
+        }
+    }
+
+    pub fn clear_jobs(&mut self) {
+        self.jobs.clear();
+        self.jobs_run_id = None;
+        self.loading_jobs = false;
+        self.selected_job = None;
+        self.selected_step = None;
+    }
+
+    /// Show the jobs fetched for `run_id`. Refreshed jobs of the same run
+    /// keep the selected job and step.
+    pub fn set_jobs(&mut self, run_id: u64, jobs: Vec<crate::github::WorkflowJob>) {
+        let selected_id = if self.jobs_run_id == Some(run_id) {
+            self.selected_job
+                .and_then(|i| self.jobs.get(i))
+                .map(|job| job.id)
+        } else {
+            self.selected_step = None;
+            None
+        };
+
+        self.jobs = jobs;
+        self.jobs_run_id = Some(run_id);
+        self.selected_job = selected_id.and_then(|id| self.jobs.iter().position(|job| job.id == id));
+        match self.selected_job.map(|i| self.jobs[i].steps.len()) {
+            Some(0) | None => self.selected_step = None,
+            Some(steps) => self.selected_step = self.selected_step.map(|i| i.min(steps - 1)),
+        }
+    }
+
+    /// Move the step selection while a job's steps are open, otherwise the
+    /// job selection.
+    pub fn move_job_selection(&mut self, direction: i32) {
+        let (len, selected) = match (self.selected_job, self.selected_step) {
+            (Some(job), Some(_)) => (self.jobs[job].steps.len(), &mut self.selected_step),
+            _ => (self.jobs.len(), &mut self.selected_job),
+        };
+        if len == 0 {
+            return;
+        }
+        *selected = Some(match *selected {
+            None => 0,
+            Some(current) => (current as i32 + direction).clamp(0, len as i32 - 1) as usize,
+        });
+    }
+
+    /// Open the selected job's steps. Returns `false` when there is no job
+    /// with steps to open.
+    pub fn open_job_steps(&mut self) -> bool {
+        let has_steps = self
+            .selected_job
+            .and_then(|i| self.jobs.get(i))
+            .is_some_and(|job| !job.steps.is_empty());
+        if has_steps && self.selected_step.is_none() {
+            self.selected_step = Some(0);
+            return true;
+        }
+        false
+    }
+
+    /// Go back from a job's steps to the job list. Returns `false` when no
+    /// steps were open.
+    pub fn close_job_steps(&mut self) -> bool {
+        self.selected_step.take().is_some()
+    }
+
+    /// Merge a freshly fetched first page of runs into the loaded ones:
+    /// known runs are updated in place, new runs go on top, and the
+    /// selection stays on the same run.
+    pub fn merge_latest_runs(&mut self, page: crate::github::WorkflowRunPage) {
+        let selected_id = self
+            .selected_action
+            .and_then(|i| self.actions.get(i))
+            .map(|run| run.id);
+
+        self.note_workflow_runs(&page.runs);
+        let mut new_runs = Vec::new();
+        for run in page.runs {
+            match self.actions.iter_mut().find(|loaded| loaded.id == run.id) {
+                Some(loaded) => *loaded = run,
+                None => new_runs.push(run),
+            }
+        }
+        self.actions.splice(0..0, new_runs);
+        self.actions_page = self.actions_page.max(1);
+        self.actions_total = page.total_count;
+
+        if let Some(id) = selected_id {
+            self.selected_action = self.actions.iter().position(|run| run.id == id);
+        }
+    }
+
+    /// Poll quickly while a dispatched run is awaited or any loaded run is
+    /// queued or in progress, slowly once everything has completed.
+    pub fn refresh_interval(&self) -> Duration {
+        let refresh = &crate::config::get().refresh;
+        let secs = if self.pending_dispatch.is_some()
+            || self.actions.iter().any(|run| !run.is_completed())
+        {
+            refresh.active_secs
+        } else {
+            refresh.idle_secs
+        };
+        Duration::from_secs(secs.get())
+    }
+
+    pub fn schedule_refresh(&mut self) {
+        self.next_refresh = Some(Instant::now() + self.refresh_interval());
+    }
+
     pub fn switch_panel(&mut self, direction: i32) {
-        let panels = [Panel::Repositories, Panel::Actions, Panel::Details];
+        let panels = [
+            Panel::Repositories,
+            Panel::Workflows,
+            Panel::Actions,
+            Panel::Details,
+        ];
         let current_index = panels
             .iter()
             .position(|&p| p == self.current_panel)
@@ -60,3 +537,96 @@ impl AppState {
         self.current_panel = panels[new_index];
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ACTIVE_REFRESH_SECS, IDLE_REFRESH_SECS};
+    use crate::github::{WorkflowRun, WorkflowRunPage};
+    use chrono::Utc;
+
+    fn run(id: u64, status: &str) -> WorkflowRun {
+        WorkflowRun {
+            id,
+            run_number: id,
+            name: "CI".to_string(),
+            workflow_id: 1,
+            event: "push".to_string(),
+            head_branch: Some("main".to_string()),
+            head_sha: "abc123".to_string(),
+            actor: None,
+            status: status.to_string(),
+            conclusion: (status == "completed").then(|| "success".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            run_started_at: None,
+            html_url: String::new(),
+        }
+    }
+
+    fn page(runs: Vec<WorkflowRun>, total_count: u64) -> WorkflowRunPage {
+        WorkflowRunPage { runs, total_count }
+    }
+
+    fn ids(app: &AppState) -> Vec<u64> {
+        app.actions.iter().map(|run| run.id).collect()
+    }
+
+    #[test]
+    fn merge_keeps_updated_new_and_paged_in_runs() {
+        let mut app = AppState::new();
+        // Runs 2 and 3 came in with the second page
+        app.actions = vec![
+            run(5, "in_progress"),
+            run(4, "completed"),
+            run(3, "completed"),
+            run(2, "completed"),
+        ];
+        app.actions_page = 2;
+        app.selected_action = Some(2);
+
+        app.merge_latest_runs(page(
+            vec![run(7, "queued"), run(6, "queued"), run(5, "completed"), run(4, "completed")],
+            6,
+        ));
+
+        assert_eq!(ids(&app), [7, 6, 5, 4, 3, 2]);
+        assert!(app.actions[2].is_completed());
+        assert_eq!(app.actions_page, 2);
+        assert_eq!(app.actions_total, 6);
+        // Still on run 3, two rows further down
+        assert_eq!(app.selected_action, Some(4));
+        assert_eq!(app.workflow_last_runs[&1].id, 7);
+    }
+
+    #[test]
+    fn merge_of_an_unchanged_page_duplicates_nothing() {
+        let mut app = AppState::new();
+        let latest = vec![run(3, "completed"), run(2, "completed")];
+        app.merge_latest_runs(page(latest.clone(), 2));
+        app.merge_latest_runs(page(latest, 2));
+
+        assert_eq!(ids(&app), [3, 2]);
+        assert_eq!(app.actions_page, 1);
+        assert_eq!(app.selected_action, None);
+    }
+
+    #[test]
+    fn polls_quickly_while_runs_are_active() {
+        let active = Duration::from_secs(ACTIVE_REFRESH_SECS);
+        let idle = Duration::from_secs(IDLE_REFRESH_SECS);
+
+        let mut app = AppState::new();
+        assert_eq!(app.refresh_interval(), idle);
+
+        app.actions = vec![run(2, "completed"), run(1, "completed")];
+        assert_eq!(app.refresh_interval(), idle);
+
+        app.actions.push(run(0, "queued"));
+        assert_eq!(app.refresh_interval(), active);
+
+        app.actions.pop();
+        app.pending_dispatch = Some(PendingDispatch::new(1, "main".to_string(), 2));
+        assert_eq!(app.refresh_interval(), active);
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::future::Future;
+use octocrab::etag::Etagged;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::JoinHandle;
+
+use crate::auth::{AuthCodeLogin, DeviceAuthorization, DevicePoll, TokenData, TokenInfo};
+use crate::github::{RepoWithActions, Workflow, WorkflowJob, WorkflowRunPage};
+use crate::workflow::DispatchInput;
+use crate::utils::error::AppError;
+use super::event::Event;
+use super::log_view::LogChunk;
+use super::yaml_view::WorkflowSource;
+use super::state::PendingDispatch;
+
+/// Background work the UI waits on. At most one task of each kind runs at a
+/// time; starting another cancels the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskKind {
+    /// Validating the stored session.
+    Session,
+    /// Refreshing the session's token before it expires.
+    TokenRefresh,
+    /// Any of the login flows.
+    Login,
+    Repos,
+    Workflows,
+    /// Enabling or disabling a workflow.
+    WorkflowState,
+    /// The first page of runs, on selection or by a poll.
+    Runs,
+    /// A further page of runs, kept apart so polls don't cancel it.
+    RunsPage,
+    Jobs,
+    Logs,
+    WorkflowFile,
+    /// Re-running or cancelling a run.
+    RunAction,
+    /// Any step of dispatching a workflow.
+    Dispatch,
+}
+
+/// What a finished background task sends back to the main loop.
+#[derive(Debug)]
+pub enum TaskResult {
+    SessionRestored(Result<(TokenData, TokenInfo), AppError>),
+    TokenRefreshed(Result<TokenData, AppError>),
+    LoggedIn {
+        result: Result<TokenData, AppError>,
+        /// Reopen the token prompt on failure rather than reporting in the
+        /// header.
+        from_token_input: bool,
+    },
+    /// The callback server is up and the browser was opened, or failed to.
+    BrowserLoginStarted(Result<AuthCodeLogin, AppError>),
+    DeviceCode(Result<DeviceAuthorization, AppError>),
+    DevicePolled {
+        authorization: DeviceAuthorization,
+        result: Result<DevicePoll, AppError>,
+    },
+    ReposLoaded(Result<Vec<RepoWithActions>, AppError>),
+    WorkflowsLoaded {
+        /// Full name of the repository the workflows belong to.
+        repo: String,
+        result: Result<Vec<Workflow>, AppError>,
+    },
+    /// A workflow was enabled or disabled, or GitHub refused; `previous` is
+    /// the state to restore then.
+    WorkflowStateChanged {
+        workflow_id: u64,
+        previous: String,
+        result: Result<(), AppError>,
+    },
+    /// The first page of runs, fetched on selection or by a poll; no value
+    /// when it hasn't changed since the last poll.
+    RunsRefreshed {
+        /// Full name of the repository the runs belong to.
+        repo: String,
+        /// Workflow the runs were filtered by.
+        workflow_id: Option<u64>,
+        result: Result<Etagged<WorkflowRunPage>, AppError>,
+    },
+    JobsLoaded {
+        run_id: u64,
+        result: Result<Vec<WorkflowJob>, AppError>,
+    },
+    /// Output of a job's log from byte `offset` on, already split into
+    /// lines off the UI thread.
+    LogLoaded {
+        job_id: u64,
+        offset: usize,
+        result: Result<LogChunk, AppError>,
+    },
+    /// The workflow file at `path`, read at `git_ref`.
+    WorkflowFileLoaded {
+        path: String,
+        git_ref: String,
+        result: Result<WorkflowSource, AppError>,
+    },
+    /// A re-run or cancellation was accepted, or refused.
+    RunActionDone(Result<(), AppError>),
+    DispatchWorkflowsLoaded(Result<Vec<Workflow>, AppError>),
+    DispatchRefsLoaded(Result<Vec<String>, AppError>),
+    /// The inputs of the chosen workflow at the chosen ref; `None` when it
+    /// has no `workflow_dispatch` trigger there.
+    DispatchInputsLoaded(Result<Option<Vec<DispatchInput>>, AppError>),
+    Dispatched {
+        pending: PendingDispatch,
+        result: Result<(), AppError>,
+    },
+    /// A further page of runs, fetched when scrolling past the loaded ones.
+    RunsLoaded {
+        /// Full name of the repository the runs belong to.
+        repo: String,
+        page: u32,
+        result: Result<WorkflowRunPage, AppError>,
+    },
+}
+
+impl TaskResult {
+    pub fn kind(&self) -> TaskKind {
+        match self {
+            TaskResult::SessionRestored(_) => TaskKind::Session,
+            TaskResult::TokenRefreshed(_) => TaskKind::TokenRefresh,
+            TaskResult::LoggedIn { .. }
+            | TaskResult::BrowserLoginStarted(_)
+            | TaskResult::DeviceCode(_)
+            | TaskResult::DevicePolled { .. } => TaskKind::Login,
+            TaskResult::ReposLoaded(_) => TaskKind::Repos,
+            TaskResult::WorkflowsLoaded { .. } => TaskKind::Workflows,
+            TaskResult::WorkflowStateChanged { .. } => TaskKind::WorkflowState,
+            TaskResult::RunsRefreshed { .. } => TaskKind::Runs,
+            TaskResult::RunsLoaded { .. } => TaskKind::RunsPage,
+            TaskResult::JobsLoaded { .. } => TaskKind::Jobs,
+            TaskResult::LogLoaded { .. } => TaskKind::Logs,
+            TaskResult::WorkflowFileLoaded { .. } => TaskKind::WorkflowFile,
+            TaskResult::RunActionDone(_) => TaskKind::RunAction,
+            TaskResult::DispatchWorkflowsLoaded(_)
+            | TaskResult::DispatchRefsLoaded(_)
+            | TaskResult::DispatchInputsLoaded(_)
+            | TaskResult::Dispatched { .. } => TaskKind::Dispatch,
+        }
+    }
+}
+
+/// Spawns background tasks and keeps their handles so they can be
+/// cancelled.
+pub struct Tasks {
+    tx: UnboundedSender<Event>,
+    running: HashMap<TaskKind, (u64, JoinHandle<()>)>,
+    next_id: u64,
+}
+
+impl Tasks {
+    pub fn new(tx: UnboundedSender<Event>) -> Self {
+        Self {
+            tx,
+            running: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Run `future` in the background, cancelling any running task of the
+    /// same kind, and deliver its result as an [`Event::Task`].
+    pub fn spawn<F>(&mut self, kind: TaskKind, future: F)
+    where
+        F: Future<Output = TaskResult> + Send + 'static,
+    {
+        self.cancel(kind);
+        self.next_id += 1;
+        let id = self.next_id;
+        let tx = self.tx.clone();
+        let handle = tokio::spawn(async move {
+            let result = future.await;
+            let _ = tx.send(Event::Task {
+                id,
+                result: Box::new(result),
+            });
+        });
+        self.running.insert(kind, (id, handle));
+    }
+
+    pub fn cancel(&mut self, kind: TaskKind) {
+        if let Some((_, handle)) = self.running.remove(&kind) {
+            handle.abort();
+        }
+    }
+
+    pub fn cancel_all(&mut self) {
+        for (_, (_, handle)) in self.running.drain() {
+            handle.abort();
+        }
+    }
+
+    pub fn is_running(&self, kind: TaskKind) -> bool {
+        self.running.contains_key(&kind)
+    }
+
+    /// Mark the task `id` as done. Returns `false` when it was cancelled or
+    /// replaced before its result arrived, in which case the result is stale
+    /// and should be dropped.
+    pub fn finished(&mut self, kind: TaskKind, id: u64) -> bool {
+        match self.running.get(&kind) {
+            Some((current, _)) if *current == id => {
+                self.running.remove(&kind);
+                true
+            }
+            _ => false,
+        }
+    }
+}
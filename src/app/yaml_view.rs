@@ -0,0 +1,182 @@
+use std::cell::Cell;
+
+use crate::workflow::WorkflowFile;
+
+/// A workflow file as shown by the viewer: its lines, and the file parsed
+/// for finding jobs in it.
+#[derive(Debug)]
+pub struct WorkflowSource {
+    pub lines: Vec<String>,
+    /// Lines inside a block scalar such as the script of `run: |`, which
+    /// are text rather than YAML.
+    pub block_lines: Vec<bool>,
+    /// The parsed file, or why it couldn't be parsed. The lines are shown
+    /// either way.
+    pub file: Result<WorkflowFile, String>,
+}
+
+impl WorkflowSource {
+    pub fn parse(text: &str) -> Self {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        Self {
+            block_lines: block_scalar_lines(&lines),
+            lines,
+            file: WorkflowFile::parse(text).map_err(|e| e.user_message()),
+        }
+    }
+
+    /// Lines (0-based) the jobs are defined on, in order.
+    fn job_lines(&self) -> Vec<usize> {
+        let Ok(file) = &self.file else {
+            return Vec::new();
+        };
+        file.jobs().iter().map(|job| job.mark.line - 1).collect()
+    }
+}
+
+/// Which of `lines` belong to block scalars (`|` or `>`): those after the
+/// opening line that are blank or indented past the key opening it.
+fn block_scalar_lines(lines: &[String]) -> Vec<bool> {
+    let mut in_block = Vec::with_capacity(lines.len());
+    let mut key_column = None;
+    for line in lines {
+        let code = line.trim_start();
+        let indent = line.len() - code.len();
+        if let Some(column) = key_column {
+            if code.is_empty() || indent > column {
+                in_block.push(true);
+                continue;
+            }
+            key_column = None;
+        }
+        in_block.push(false);
+
+        // The key sits after any `- ` of the sequences the line is an item of
+        let mut key = code;
+        while let Some(rest) = key.strip_prefix("- ") {
+            key = rest.trim_start();
+        }
+        let code = code.split(" #").next().unwrap_or(code).trim_end();
+        let (before, indicator) = code.rsplit_once(' ').unwrap_or(("", code));
+        let opens_block = indicator.starts_with(['|', '>'])
+            && indicator.len() <= 3
+            && indicator[1..].chars().all(|c| matches!(c, '-' | '+' | '1'..='9'))
+            && (before.is_empty() || before.ends_with(':') || before.ends_with('-'));
+        if opens_block {
+            key_column = Some(line.len() - key.len());
+        }
+    }
+    in_block
+}
+
+/// Where the YAML viewer stands with its file.
+#[derive(Debug)]
+pub enum YamlState {
+    Loading,
+    Failed(String),
+    Loaded(WorkflowSource),
+}
+
+/// State of the full-screen viewer for a workflow file.
+#[derive(Debug)]
+pub struct YamlView {
+    pub path: String,
+    /// Branch or commit the file is read at.
+    pub git_ref: String,
+    pub state: YamlState,
+    /// Line under the cursor.
+    pub cursor: usize,
+    /// First row drawn; adjusted while rendering to keep the cursor on
+    /// screen.
+    pub scroll: Cell<usize>,
+    /// Rows drawn by the last render, for paging.
+    pub height: Cell<usize>,
+    /// Job of a run whose definition is shown once the file arrives.
+    pub job: Option<String>,
+    /// Feedback such as a parse error, cleared on the next key.
+    pub message: Option<String>,
+}
+
+impl YamlView {
+    pub fn new(path: String, git_ref: String, job: Option<String>) -> Self {
+        Self {
+            path,
+            git_ref,
+            state: YamlState::Loading,
+            cursor: 0,
+            scroll: Cell::new(0),
+            height: Cell::new(0),
+            job,
+            message: None,
+        }
+    }
+
+    /// Show the downloaded file, at the definition of the job asked for.
+    pub fn load(&mut self, source: WorkflowSource) {
+        match &source.file {
+            Ok(file) => {
+                if let Some(job) = &self.job {
+                    match file.jobs().iter().find(|definition| definition.runs_as(job)) {
+                        Some(definition) => {
+                            self.cursor = definition.mark.line - 1;
+                            // With the job at the top of the screen
+                            self.scroll.set(self.cursor);
+                        }
+                        None => {
+                            self.message = Some(format!("No definition found for job '{}'", job))
+                        }
+                    }
+                }
+            }
+            Err(e) => self.message = Some(e.clone()),
+        }
+        self.state = YamlState::Loaded(source);
+    }
+
+    pub fn source(&self) -> Option<&WorkflowSource> {
+        match &self.state {
+            YamlState::Loaded(source) => Some(source),
+            _ => None,
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.source().map_or(0, |source| source.lines.len())
+    }
+
+    pub fn move_cursor(&mut self, delta: isize) {
+        let last = self.line_count().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn page(&mut self, direction: isize) {
+        let height = self.height.get().max(1) as isize;
+        self.move_cursor(direction * height);
+    }
+
+    pub fn jump_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn jump_to_end(&mut self) {
+        self.cursor = self.line_count().saturating_sub(1);
+    }
+
+    /// Move to the definition of the next (or previous) job.
+    pub fn next_job(&mut self, forward: bool) {
+        let lines = self.source().map(WorkflowSource::job_lines).unwrap_or_default();
+        let target = if forward {
+            lines.into_iter().find(|&line| line > self.cursor)
+        } else {
+            lines.into_iter().rev().find(|&line| line < self.cursor)
+        };
+        match target {
+            Some(line) => {
+                self.cursor = line;
+                self.scroll.set(line);
+            }
+            None => self.message = Some("No more jobs".to_string()),
+        }
+    }
+}
+
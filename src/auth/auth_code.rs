@@ -1,28 +1,140 @@
-use anyhow::Result;
-use std::net::{TcpListener, SocketAddr};
 use std::time::Duration;
+use oauth2::{
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EndpointNotSet, EndpointSet,
+    PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope, TokenResponse, TokenUrl,
+    basic::BasicClient,
+};
 use tokio::sync::oneshot;
-use tiny_http::Response;
-use url::Url;
 
-use crate::config::{DEFAULT_PORT, PORT_RANGE, SERVER_TIMEOUT_SECS};
+use crate::config::{self, GitHubHost};
+use crate::github::GitHubClient;
 use crate::utils::error::AppError;
+use super::callback_server::{bind_callback_server, start_callback_server};
+use super::token_manager::TokenData;
 
-pub struct AuthResult {
-    pub access_token: String,
-    pub username: String,
+/// A browser login waiting for GitHub to redirect back to the local
+/// callback server.
+#[derive(Debug)]
+pub struct AuthCodeLogin {
+    /// GitHub's authorization page, for the user to visit when the browser
+    /// couldn't be opened.
+    pub authorize_url: String,
+    /// Why the browser couldn't be opened, if it couldn't.
+    pub browser_error: Option<String>,
+    host: GitHubHost,
+    redirect_url: String,
+    pkce_verifier: PkceCodeVerifier,
+    code_rx: oneshot::Receiver<Result<AuthorizationCode, AppError>>,
 }
 
-pub async fn auth_code_flow() -> Result<AuthResult, AppError> {
-    // For now, implement a simpler token-based flow
-    // TODO: Implement proper OAuth2 with GitHub
-    
-    println!("Note: Full OAuth2 flow needs to be implemented with updated oauth2 crate");
-    println!("For now, please configure a GitHub personal access token");
-    
-    // Return a mock result for testing
-    Ok(AuthResult {
-        access_token: "mock_token".to_string(),
-        username: "mock_user".to_string(),
+/// Start the OAuth2 authorization-code flow with PKCE.
+///
+/// Starts the local callback server and opens the user's browser on
+/// GitHub's authorization page. A browser that fails to open doesn't end
+/// the login: the page can still be visited by hand until the server times
+/// out. Finish with [`finish_auth_code_flow`].
+pub fn start_auth_code_flow(host: &GitHubHost) -> Result<AuthCodeLogin, AppError> {
+    let (server, port) = bind_callback_server()?;
+    let redirect_url = format!("http://127.0.0.1:{}/callback", port);
+    let client = oauth_client(host, &redirect_url)?;
+
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    let (authorize_url, csrf_token) = client
+        .authorize_url(CsrfToken::new_random)
+        .add_scopes(config::get().auth.scopes.iter().map(|scope| Scope::new(scope.clone())))
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    let code_rx = start_callback_server(server, csrf_token);
+    let browser_error = webbrowser::open(authorize_url.as_str())
+        .err()
+        .map(|e| e.to_string());
+
+    Ok(AuthCodeLogin {
+        authorize_url: authorize_url.to_string(),
+        browser_error,
+        host: host.clone(),
+        redirect_url,
+        pkce_verifier,
+        code_rx,
     })
-}
\ No newline at end of file
+}
+
+/// Wait for GitHub to redirect back with an authorization code and exchange
+/// it for an access token. Dropping the future stops the callback server.
+pub async fn finish_auth_code_flow(login: AuthCodeLogin) -> Result<TokenData, AppError> {
+    let AuthCodeLogin {
+        host,
+        redirect_url,
+        pkce_verifier,
+        code_rx,
+        ..
+    } = login;
+
+    // The server enforces the timeout itself; this guards against it dying silently
+    let code: AuthorizationCode = tokio::time::timeout(
+        Duration::from_secs(config::get().auth.server_timeout_secs.get() + 5),
+        code_rx,
+    )
+    .await
+    .map_err(|_| AppError::AuthTimeout)?
+    .map_err(|_| AppError::ServerError("callback server stopped unexpectedly".to_string()))??;
+
+    let token_response = oauth_client(&host, &redirect_url)?
+        .exchange_code(code)
+        .set_pkce_verifier(pkce_verifier)
+        .request_async(&oauth_http_client()?)
+        .await
+        .map_err(|e| AppError::OAuthError(e.to_string()))?;
+
+    let access_token = token_response.access_token().secret().clone();
+    let username = get_username(&host, &access_token).await?;
+
+    let mut token_data = TokenData::new(access_token, username, &host);
+    token_data.token_type = token_response.token_type().as_ref().to_string();
+    // GitHub separates scopes with commas rather than the spaces oauth2 expects
+    token_data.scopes = token_response.scopes().map(|scopes| {
+        scopes
+            .iter()
+            .flat_map(|scope| scope.split(','))
+            .map(|scope| scope.trim().to_string())
+            .filter(|scope| !scope.is_empty())
+            .collect()
+    });
+    token_data.expires_at = token_response
+        .expires_in()
+        .and_then(|expires_in| chrono::Duration::from_std(expires_in).ok())
+        .map(|expires_in| token_data.created_at + expires_in);
+    token_data.refresh_token = token_response
+        .refresh_token()
+        .map(|refresh_token| refresh_token.secret().clone());
+
+    Ok(token_data)
+}
+
+type AuthCodeClient =
+    BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>;
+
+fn oauth_client(host: &GitHubHost, redirect_url: &str) -> Result<AuthCodeClient, AppError> {
+    let mut client = BasicClient::new(ClientId::new(host.client_id().to_string()))
+        .set_auth_uri(AuthUrl::new(host.auth_url())?)
+        .set_token_uri(TokenUrl::new(host.token_url())?)
+        .set_redirect_uri(RedirectUrl::new(redirect_url.to_string())?);
+    if !host.client_secret().is_empty() {
+        client = client.set_client_secret(ClientSecret::new(host.client_secret().to_string()));
+    }
+    Ok(client)
+}
+
+/// Look up the login of the user that owns `access_token` on `host`.
+pub async fn get_username(host: &GitHubHost, access_token: &str) -> Result<String, AppError> {
+    Ok(GitHubClient::new(host, access_token).await?.username)
+}
+
+/// HTTP client for talking to the OAuth endpoints. Redirects are disabled
+/// as recommended by the oauth2 crate to avoid SSRF.
+pub(super) fn oauth_http_client() -> Result<reqwest::Client, AppError> {
+    Ok(reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?)
+}
@@ -1,84 +1,122 @@
-use anyhow::Result;
-use std::sync::Arc;
-use std::time::Duration;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 use tokio::sync::oneshot;
 use tiny_http::{Server, Response};
 use url::Url;
-use oauth2::AuthorizationCode;
+use oauth2::{AuthorizationCode, CsrfToken};
 
-use crate::config::SERVER_TIMEOUT_SECS;
+use crate::config;
 use crate::utils::error::AppError;
 
-/// Start local HTTP server to handle OAuth callback
-pub async fn start_callback_server(port: u16) -> Result<oneshot::Receiver<AuthorizationCode>, AppError> {
-    let (tx, rx) = oneshot::channel::<AuthorizationCode>();
-    let csrf_token = oauth2::CsrfToken::new_random();
-    let csrf_token_clone = csrf_token.clone();
-    
-    let server = Arc::new(
-        Server::http(format!("127.0.0.1:{}", port))
-            .map_err(|e| AppError::ServerError(e.to_string()))?
-    );
-    
-    tokio::spawn(async move {
-        let mut attempts = 0;
-        let max_attempts = (SERVER_TIMEOUT_SECS * 2) as usize;
-        
-        for request in server.incoming_requests() {
-            if attempts > max_attempts {
-                let _ = tx.send(Err(AppError::AuthTimeout));
-                break;
+/// How often the server checks whether the login was abandoned, so a
+/// cancelled login releases its port promptly.
+const CANCEL_CHECK: Duration = Duration::from_millis(500);
+
+/// Bind the callback server to the first free port in the configured range
+/// and return it with its port. The server keeps the port from then on, so
+/// nothing else can take it before GitHub redirects back.
+pub fn bind_callback_server() -> Result<(Server, u16), AppError> {
+    let auth_config = &config::get().auth;
+    let first = auth_config.callback_port.get();
+    (first..first.saturating_add(auth_config.port_range.get()))
+        .find_map(|port| {
+            let server = Server::http(SocketAddr::from(([127, 0, 0, 1], port))).ok()?;
+            Some((server, port))
+        })
+        .ok_or(AppError::NoAvailablePorts)
+}
+
+/// Handle the OAuth callback on `server`, from [`bind_callback_server`].
+///
+/// The returned receiver resolves with the authorization code once GitHub
+/// redirects back with a `state` matching `csrf_token`, or with an error if
+/// the user denies access or the configured server timeout elapses first.
+pub fn start_callback_server(
+    server: Server,
+    csrf_token: CsrfToken,
+) -> oneshot::Receiver<Result<AuthorizationCode, AppError>> {
+    let (tx, rx) = oneshot::channel::<Result<AuthorizationCode, AppError>>();
+
+    // tiny_http is blocking, so keep it off the async worker threads
+    tokio::task::spawn_blocking(move || {
+        let deadline = Instant::now() + Duration::from_secs(config::get().auth.server_timeout_secs.get());
+
+        let result = loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break Err(AppError::AuthTimeout);
             }
-            
+
+            // The receiver is dropped when the login is cancelled
+            if tx.is_closed() {
+                return;
+            }
+
+            let request = match server.recv_timeout(remaining.min(CANCEL_CHECK)) {
+                Ok(Some(request)) => request,
+                Ok(None) => continue,
+                Err(e) => break Err(AppError::ServerError(e.to_string())),
+            };
+
             let url = format!("http://localhost{}", request.url());
-            if let Ok(parsed_url) = Url::parse(&url) {
-                // Handle successful authorization
-                if let Some(code) = parsed_url.query_pairs()
-                    .find(|(key, _)| key == "code")
-                    .map(|(_, value)| AuthorizationCode::new(value.into_owned())) 
-                {
-                    // Verify CSRF token
-                    if let Some(returned_state) = parsed_url.query_pairs()
-                        .find(|(key, _)| key == "state")
-                        .map(|(_, value)| value.into_owned()) 
-                    {
-                        if returned_state == csrf_token_clone.secret() {
-                            let response = Response::from_string(
-                                "Authentication successful! You can close this window."
-                            ).with_status_code(200);
-                            let _ = request.respond(response);
-                            let _ = tx.send(Ok(code));
-                            break;
-                        } else {
-                            let response = Response::from_string(
-                                "Security error: Invalid state parameter."
-                            ).with_status_code(400);
-                            let _ = request.respond(response);
-                        }
-                    }
-                } 
-                // Handle authorization denial
-                else if let Some(error) = parsed_url.query_pairs()
-                    .find(|(key, _)| key == "error")
-                    .map(|(_, value)| value.into_owned()) 
-                {
-                    let message = match error.as_str() {
-                        "access_denied" => "You denied access to the application.",
-                        _ => "Authentication failed.",
-                    };
-                    
-                    let response = Response::from_string(
-                        format!("Error: {}", message)
-                    ).with_status_code(400);
-                    let _ = request.respond(response);
-                    let _ = tx.send(Err(AppError::UserCancelled));
-                    break;
-                }
+            let Ok(parsed_url) = Url::parse(&url) else {
+                let _ = request.respond(Response::empty(400));
+                continue;
+            };
+            let query = |name: &str| {
+                parsed_url
+                    .query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned())
+            };
+
+            let code = query("code");
+            let error = query("error");
+            // Ignore unrelated requests such as /favicon.ico
+            if code.is_none() && error.is_none() {
+                let _ = request.respond(Response::empty(404));
+                continue;
             }
-            
-            attempts += 1;
-        }
+
+            // Verify CSRF token, so that no other page can complete or
+            // abort the login by redirecting here
+            if query("state").as_deref() != Some(csrf_token.secret().as_str()) {
+                let response = Response::from_string(
+                    "Security error: Invalid state parameter."
+                ).with_status_code(400);
+                let _ = request.respond(response);
+                continue;
+            }
+
+            // Handle successful authorization
+            if let Some(code) = code {
+                let response = Response::from_string(
+                    "Authentication successful! You can close this window."
+                ).with_status_code(200);
+                let _ = request.respond(response);
+                break Ok(AuthorizationCode::new(code));
+            }
+            // Handle authorization denial
+            else if let Some(error) = error {
+                let message = match error.as_str() {
+                    "access_denied" => "You denied access to the application.",
+                    _ => "Authentication failed.",
+                };
+
+                let response = Response::from_string(
+                    format!("Error: {}", message)
+                ).with_status_code(400);
+                let _ = request.respond(response);
+
+                break Err(match error.as_str() {
+                    "access_denied" => AppError::UserCancelled,
+                    _ => AppError::OAuthError(query("error_description").unwrap_or(error)),
+                });
+            }
+        };
+
+        let _ = tx.send(result);
     });
-    
-    Ok(rx)
-}
\ No newline at end of file
+
+    rx
+}
@@ -0,0 +1,498 @@
+use argon2::Argon2;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use chacha20poly1305::{
+    ChaCha20Poly1305, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, rand_core::RngCore},
+};
+use keyring::{Entry, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::{
+    self, CREDENTIALS_FILE, CREDENTIALS_KEY_FILE, CredentialStoreKind, SERVICE_NAME,
+};
+use crate::utils::error::AppError;
+
+/// Environment variable holding the passphrase for the encrypted file. When
+/// unset the file key is derived from the machine id and user name, which
+/// anyone on the machine can read: the file is then only obfuscated.
+/// Machines without an id get a random key file instead.
+const PASSPHRASE_ENV_VAR: &str = "OXA_PASSPHRASE";
+
+const MACHINE_ID_PATHS: &[&str] = &["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+const FILE_FORMAT_VERSION: u32 = 1;
+const SALT_LEN: usize = 16;
+const KEY_FILE_LEN: usize = 32;
+
+/// A place secrets can be stored under string keys.
+pub trait CredentialStore: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<String>, AppError>;
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError>;
+    fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+/// Open the configured credential store.
+///
+/// In `auto` mode the system keyring is used when a keyring service answers
+/// and the encrypted file otherwise, which covers minimal containers and WSL.
+pub fn open_credential_store() -> Result<Box<dyn CredentialStore>, AppError> {
+    match config::get().auth.credential_store {
+        CredentialStoreKind::Keyring => Ok(Box::new(KeyringStore)),
+        CredentialStoreKind::File => Ok(Box::new(EncryptedFileStore::new()?)),
+        CredentialStoreKind::Auto => {
+            if KeyringStore::is_available() {
+                Ok(Box::new(KeyringStore))
+            } else {
+                if passphrase().is_none() && machine_id().is_some() {
+                    log::warn!(
+                        "no keyring available; credentials are only obfuscated unless {} is set",
+                        PASSPHRASE_ENV_VAR
+                    );
+                }
+                Ok(Box::new(EncryptedFileStore::new()?))
+            }
+        }
+    }
+}
+
+/// How well the configured store protects credentials at rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreProtection {
+    Keyring,
+    /// The encrypted file, keyed by a passphrase.
+    Passphrase,
+    /// The encrypted file, keyed by the machine id and user name. Anyone
+    /// who can read the file can read those too, so this only obfuscates.
+    MachineBound,
+    /// The encrypted file, keyed by a random key stored beside it with the
+    /// same owner-only permissions, on machines without an id.
+    KeyFile,
+}
+
+impl StoreProtection {
+    /// The protection credentials get, without opening the store.
+    pub fn current() -> Self {
+        let keyring = match config::get().auth.credential_store {
+            CredentialStoreKind::Keyring => true,
+            CredentialStoreKind::File => false,
+            CredentialStoreKind::Auto => KeyringStore::is_available(),
+        };
+        if keyring {
+            StoreProtection::Keyring
+        } else if passphrase().is_some() {
+            StoreProtection::Passphrase
+        } else if machine_id().is_some() {
+            StoreProtection::MachineBound
+        } else {
+            StoreProtection::KeyFile
+        }
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            StoreProtection::Keyring => "system keyring".to_string(),
+            StoreProtection::Passphrase => {
+                format!("file encrypted with the passphrase in {}", PASSPHRASE_ENV_VAR)
+            }
+            StoreProtection::MachineBound => format!(
+                "file obfuscated with this machine's id and user name, which other \
+                 local users can read; set {} or use a keyring to encrypt it",
+                PASSPHRASE_ENV_VAR
+            ),
+            StoreProtection::KeyFile => format!(
+                "file encrypted with a random key in {} next to it, which only you \
+                 can read; set {} or use a keyring to protect it further",
+                CREDENTIALS_KEY_FILE, PASSPHRASE_ENV_VAR
+            ),
+        }
+    }
+}
+
+/// Secrets kept in the platform keyring (Secret Service, Keychain, ...)
+/// under the configured service name.
+pub struct KeyringStore;
+
+fn keyring_entry(key: &str) -> Result<Entry, AppError> {
+    Ok(Entry::new(&config::get().auth.service_name, key)?)
+}
+
+impl KeyringStore {
+    /// Probe the keyring with a lookup; a missing entry still means a
+    /// keyring service answered.
+    fn is_available() -> bool {
+        keyring_entry("availability_probe")
+            .map(|entry| matches!(entry.get_password(), Ok(_) | Err(Error::NoEntry)))
+            .unwrap_or(false)
+    }
+}
+
+impl CredentialStore for KeyringStore {
+    fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        match keyring_entry(key)?.get_password() {
+            Ok(password) => Ok(Some(password)),
+            Err(Error::NoEntry) => Ok(None),
+            Err(e) => Err(AppError::KeyringError(e)),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        keyring_entry(key)?.set_password(value)?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        match keyring_entry(key)?.delete_password() {
+            Ok(_) => Ok(()),
+            Err(Error::NoEntry) => Ok(()), // Already cleared
+            Err(e) => Err(AppError::KeyringError(e)),
+        }
+    }
+}
+
+/// Secrets kept in a ChaCha20-Poly1305 encrypted file under the user's
+/// config directory, readable only by the owner. Without a passphrase its
+/// key can be rebuilt by anyone on the machine, unless it has no machine id
+/// and a random key file is used instead.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    secret: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl EncryptedFileStore {
+    pub fn new() -> Result<Self, AppError> {
+        let dir = dirs::config_dir()
+            .ok_or_else(|| {
+                AppError::CredentialStoreError("cannot determine config directory".to_string())
+            })?
+            .join(SERVICE_NAME);
+
+        let secret = file_secret(&dir)?;
+        Ok(Self::with_secret(dir.join(CREDENTIALS_FILE), secret))
+    }
+
+    fn with_secret(path: PathBuf, secret: String) -> Self {
+        Self { path, secret }
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>, AppError> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+
+        let file: EncryptedFile = serde_json::from_slice(&fs::read(&self.path)?)?;
+        if file.version != FILE_FORMAT_VERSION {
+            return Err(AppError::CredentialStoreError(format!(
+                "unsupported credentials file version {}",
+                file.version
+            )));
+        }
+
+        let salt = decode(&file.salt)?;
+        let nonce = decode(&file.nonce)?;
+        let ciphertext = decode(&file.ciphertext)?;
+        if nonce.len() != Nonce::default().len() {
+            return Err(AppError::CredentialStoreError(
+                "malformed credentials file: bad nonce".to_string(),
+            ));
+        }
+
+        let cipher = ChaCha20Poly1305::new(&derive_key(&self.secret, &salt)?);
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| {
+                AppError::CredentialStoreError(format!(
+                    "cannot decrypt {} (wrong {} or corrupted file)",
+                    self.path.display(),
+                    PASSPHRASE_ENV_VAR
+                ))
+            })?;
+
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn save(&self, entries: &BTreeMap<String, String>) -> Result<(), AppError> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        let cipher = ChaCha20Poly1305::new(&derive_key(&self.secret, &salt)?);
+        let ciphertext = cipher
+            .encrypt(&nonce, serde_json::to_vec(entries)?.as_slice())
+            .map_err(|_| AppError::CredentialStoreError("encryption failed".to_string()))?;
+
+        let file = EncryptedFile {
+            version: FILE_FORMAT_VERSION,
+            salt: BASE64.encode(salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        write_private(&self.path, &serde_json::to_vec_pretty(&file)?)
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+        Ok(self.load()?.remove(key))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+        let mut entries = self.load()?;
+        entries.insert(key.to_string(), value.to_string());
+        self.save(&entries)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        let mut entries = self.load()?;
+        if entries.remove(key).is_some() {
+            self.save(&entries)?;
+        }
+        Ok(())
+    }
+}
+
+fn passphrase() -> Option<String> {
+    std::env::var(PASSPHRASE_ENV_VAR)
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+}
+
+/// The passphrase from the environment, or else a secret made from this
+/// machine's id and the user name. The latter only stops the file being
+/// read when copied to another machine: both parts are readable by anyone
+/// on this one, so it is obfuscation rather than encryption. Without a
+/// machine id, as in many containers, a random key file in `dir` is used.
+fn file_secret(dir: &Path) -> Result<String, AppError> {
+    if let Some(passphrase) = passphrase() {
+        return Ok(passphrase);
+    }
+
+    let Some(machine_id) = machine_id() else {
+        return key_file_secret(&dir.join(CREDENTIALS_KEY_FILE));
+    };
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+
+    Ok(format!("{}:{}:{}", SERVICE_NAME, machine_id, user))
+}
+
+fn machine_id() -> Option<String> {
+    MACHINE_ID_PATHS
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// The key stored at `path`, created with owner-only permissions on first
+/// use.
+fn key_file_secret(path: &Path) -> Result<String, AppError> {
+    if path.exists() {
+        let key = fs::read_to_string(path)?.trim().to_string();
+        if key.is_empty() {
+            return Err(AppError::CredentialStoreError(format!(
+                "{} is empty",
+                path.display()
+            )));
+        }
+        return Ok(key);
+    }
+
+    let mut key = [0u8; KEY_FILE_LEN];
+    OsRng.fill_bytes(&mut key);
+    let key = BASE64.encode(key);
+    write_private(path, key.as_bytes())?;
+    Ok(key)
+}
+
+fn derive_key(secret: &str, salt: &[u8]) -> Result<Key, AppError> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(secret.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::CredentialStoreError(format!("key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, AppError> {
+    BASE64
+        .decode(value)
+        .map_err(|e| AppError::CredentialStoreError(format!("malformed credentials file: {}", e)))
+}
+
+/// Atomically replace `path` with `contents`, creating it with 0600
+/// permissions (and its directory with 0700) on Unix.
+fn write_private(path: &Path, contents: &[u8]) -> Result<(), AppError> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(dir)?;
+
+    let tmp_path = path.with_extension("tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+
+    let mut file = options.open(&tmp_path)?;
+    #[cfg(unix)]
+    {
+        // `mode` only applies on creation; tighten a pre-existing temp file too
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp dir, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("oxa-credential-store-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&dir);
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn open(dir: &TempDir, secret: &str) -> EncryptedFileStore {
+        EncryptedFileStore::with_secret(dir.0.join(CREDENTIALS_FILE), secret.to_string())
+    }
+
+    fn assert_store_error(result: Result<Option<String>, AppError>) {
+        assert!(
+            matches!(result, Err(AppError::CredentialStoreError(_))),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = TempDir::new("round-trip");
+        let store = open(&dir, "correct horse");
+        assert_eq!(store.get("token").unwrap(), None);
+
+        store.set("token", "secret-value").unwrap();
+        store.set("accounts", "[]").unwrap();
+        assert_eq!(store.get("token").unwrap().as_deref(), Some("secret-value"));
+
+        // Nothing is stored in the clear
+        let contents = fs::read_to_string(dir.0.join(CREDENTIALS_FILE)).unwrap();
+        assert!(!contents.contains("secret-value"));
+
+        store.delete("token").unwrap();
+        store.delete("token").unwrap();
+        let reopened = open(&dir, "correct horse");
+        assert_eq!(reopened.get("token").unwrap(), None);
+        assert_eq!(reopened.get("accounts").unwrap().as_deref(), Some("[]"));
+    }
+
+    #[test]
+    fn wrong_secret_is_refused() {
+        let dir = TempDir::new("wrong-secret");
+        open(&dir, "correct horse").set("token", "secret-value").unwrap();
+
+        let store = open(&dir, "battery staple");
+        assert_store_error(store.get("token"));
+        // A failed read must not be taken for an empty store and overwritten
+        assert!(store.set("other", "value").is_err());
+        assert_store_error(store.get("token"));
+    }
+
+    #[test]
+    fn corrupt_file_is_refused() {
+        let dir = TempDir::new("corrupt");
+        let store = open(&dir, "correct horse");
+        store.set("token", "secret-value").unwrap();
+        let path = dir.0.join(CREDENTIALS_FILE);
+        let file: EncryptedFile = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+
+        let write = |file: &EncryptedFile| {
+            fs::write(&path, serde_json::to_vec(file).unwrap()).unwrap();
+        };
+        let mut ciphertext = decode(&file.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        write(&EncryptedFile {
+            ciphertext: BASE64.encode(&ciphertext),
+            ..file
+        });
+        assert_store_error(store.get("token"));
+
+        write(&EncryptedFile {
+            version: FILE_FORMAT_VERSION,
+            salt: BASE64.encode([0u8; SALT_LEN]),
+            nonce: BASE64.encode([0u8; 3]),
+            ciphertext: BASE64.encode(&ciphertext),
+        });
+        assert_store_error(store.get("token"));
+
+        write(&EncryptedFile {
+            version: FILE_FORMAT_VERSION,
+            salt: "not base64!".to_string(),
+            nonce: String::new(),
+            ciphertext: String::new(),
+        });
+        assert_store_error(store.get("token"));
+
+        write(&EncryptedFile {
+            version: FILE_FORMAT_VERSION + 1,
+            salt: String::new(),
+            nonce: String::new(),
+            ciphertext: String::new(),
+        });
+        assert_store_error(store.get("token"));
+
+        fs::write(&path, "{ truncated").unwrap();
+        assert!(matches!(store.get("token"), Err(AppError::SerializationError(_))));
+    }
+
+    #[test]
+    fn key_file_is_created_once() {
+        let dir = TempDir::new("key-file");
+        let path = dir.0.join(CREDENTIALS_KEY_FILE);
+        let key = key_file_secret(&path).unwrap();
+        assert_eq!(BASE64.decode(&key).unwrap().len(), KEY_FILE_LEN);
+        assert_eq!(key_file_secret(&path).unwrap(), key);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::write(&path, "\n").unwrap();
+        assert!(key_file_secret(&path).is_err());
+    }
+}
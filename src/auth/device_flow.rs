@@ -1,64 +1,112 @@
-use anyhow::Result;
 use oauth2::{
-    AuthorizationCode, AuthUrl, ClientId, 
-    RedirectUrl, Scope, TokenUrl,
+    ClientId, DeviceAuthorizationUrl, Scope, StandardDeviceAuthorizationResponse,
     basic::BasicClient,
-    DeviceAuthorizationUrl, StandardDeviceAuthorizationResponse,
 };
-use std::time::Duration;
-use tokio::time::sleep;
+use std::time::{Duration, Instant};
 
-use crate::config::{CLIENT_ID, AUTH_URL, TOKEN_URL, DEVICE_AUTH_URL, SCOPES};
+use crate::config::{self, GitHubHost};
 use crate::utils::error::AppError;
-use super::auth_code::{AuthResult, get_username};
+use super::auth_code::{get_username, oauth_http_client};
+use super::refresh::TokenGrant;
+use super::token_manager::TokenData;
 
-/// Perform OAuth2 device code flow
-pub async fn device_code_flow() -> Result<AuthResult, AppError> {
-    let client = BasicClient::new(
-        oauth2::ClientId::new(CLIENT_ID.to_string()),
-        None, // No client secret needed for device flow
-        oauth2::AuthUrl::new(AUTH_URL.to_string())?,
-        Some(oauth2::TokenUrl::new(TOKEN_URL.to_string())?),
-    )
-    .set_device_authorization_url(
-        DeviceAuthorizationUrl::new(DEVICE_AUTH_URL.to_string())?
-    );
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
 
-    // Add required scopes
-    let mut client = client;
-    for scope in SCOPES {
-        client = client.add_scope(oauth2::Scope::new(scope.to_string()));
-    }
+/// Extra wait GitHub asks for each time it answers `slow_down`.
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
 
-    // Request device authorization
-    let device_auth_response: StandardDeviceAuthorizationResponse = client
-        .exchange_device_code()
-        .request_async(async_http_client()).await?;
+/// A pending device authorization the user still has to approve.
+#[derive(Debug, Clone)]
+pub struct DeviceAuthorization {
+    pub verification_uri: String,
+    pub user_code: String,
+    pub expires_at: Instant,
+    pub interval: Duration,
+    pub host: GitHubHost,
+    device_code: String,
+}
 
-    // Display instructions to user
-    println!("\n🔐 GitHub Authentication Required");
-    println!("Please follow these steps to authenticate:\n");
-    println!("1. Visit: {}", device_auth_response.verification_uri());
-    println!("2. Enter code: {}\n", device_auth_response.user_code().secret());
-    println!("This code will expire in {} minutes.", device_auth_response.expires_in() / 60);
-    println!("Waiting for authorization...\n");
+/// Outcome of a single poll of the token endpoint.
+#[derive(Debug)]
+pub enum DevicePoll {
+    Pending,
+    SlowDown,
+    Complete(Box<TokenData>),
+}
 
-    // Poll for token with timeout
-    let timeout = Duration::from_secs(device_auth_response.expires_in());
-    let token_response = client
-        .exchange_device_access_token(&device_auth_response)
-        .request_async(async_http_client(), sleep, Some(timeout))
-        .await?;
+/// Request a device and user code for the OAuth2 device code flow on `host`.
+pub async fn request_device_code(host: &GitHubHost) -> Result<DeviceAuthorization, AppError> {
+    let client = BasicClient::new(ClientId::new(host.client_id().to_string()))
+        .set_device_authorization_url(DeviceAuthorizationUrl::new(host.device_auth_url())?);
 
-    // Get user information
-    let username = get_username(token_response.access_token().secret()).await?;
+    let details: StandardDeviceAuthorizationResponse = client
+        .exchange_device_code()
+        .add_scopes(config::get().auth.scopes.iter().map(|scope| Scope::new(scope.clone())))
+        .request_async(&oauth_http_client()?)
+        .await
+        .map_err(|e| AppError::OAuthError(e.to_string()))?;
 
-    Ok(AuthResult {
-        access_token: token_response.access_token().secret().clone(),
-        username,
+    Ok(DeviceAuthorization {
+        verification_uri: details.verification_uri().to_string(),
+        user_code: details.user_code().secret().clone(),
+        expires_at: Instant::now() + details.expires_in(),
+        interval: details.interval(),
+        host: host.clone(),
+        device_code: details.device_code().secret().clone(),
     })
 }
 
-async fn async_http_client() -> oauth2::reqwest::HttpClient<oauth2::reqwest::Error> {
-    oauth2::reqwest::async_http_client()
-}
\ No newline at end of file
+/// Poll the token endpoint once for `authorization`.
+///
+/// GitHub reports `authorization_pending` and friends with a 200 status,
+/// which the oauth2 crate treats as a malformed token response, so the
+/// request is made directly. On `slow_down` the interval in
+/// `authorization` is raised as the spec requires.
+pub async fn poll_device_token(
+    authorization: &mut DeviceAuthorization,
+) -> Result<DevicePoll, AppError> {
+    if Instant::now() >= authorization.expires_at {
+        return Err(AppError::AuthTimeout);
+    }
+
+    let response: TokenGrant = oauth_http_client()?
+        .post(authorization.host.token_url())
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&[
+            ("client_id", authorization.host.client_id()),
+            ("device_code", authorization.device_code.as_str()),
+            ("grant_type", DEVICE_CODE_GRANT_TYPE),
+        ])
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(access_token) = response.access_token.clone() {
+        let username = get_username(&authorization.host, &access_token).await?;
+        return Ok(DevicePoll::Complete(Box::new(response.into_token_data(
+            access_token,
+            username,
+            &authorization.host,
+        ))));
+    }
+
+    match response.error.as_deref() {
+        Some("authorization_pending") => Ok(DevicePoll::Pending),
+        Some("slow_down") => {
+            authorization.interval = response
+                .interval
+                .map(Duration::from_secs)
+                .unwrap_or(authorization.interval + SLOW_DOWN_INCREMENT);
+            Ok(DevicePoll::SlowDown)
+        }
+        Some("expired_token") => Err(AppError::AuthTimeout),
+        Some("access_denied") => Err(AppError::UserCancelled),
+        Some(error) => Err(AppError::OAuthError(
+            response.error_message().unwrap_or_else(|| error.to_string()),
+        )),
+        None => Err(AppError::OAuthError(
+            "token endpoint returned neither a token nor an error".to_string(),
+        )),
+    }
+}
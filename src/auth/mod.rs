@@ -1,7 +1,14 @@
-use anyhow::Result;
-
 pub mod auth_code;
+pub mod callback_server;
+pub mod credential_store;
+pub mod device_flow;
+pub mod personal_token;
+pub mod refresh;
 pub mod token_manager;
 
-pub use auth_code::auth_code_flow;
-pub use token_manager::{TokenManager, TokenData};
\ No newline at end of file
+pub use auth_code::{AuthCodeLogin, finish_auth_code_flow, start_auth_code_flow};
+pub use device_flow::{DeviceAuthorization, DevicePoll, poll_device_token, request_device_code};
+pub use personal_token::{
+    TokenInfo, personal_token_login, read_token_file, token_from_env, validate_token,
+};
+pub use token_manager::{Account, TokenData, TokenManager};
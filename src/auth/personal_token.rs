@@ -0,0 +1,176 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use http::HeaderMap;
+use std::path::Path;
+
+use crate::config::{self, GitHubHost};
+use crate::github::GitHubClient;
+use crate::utils::error::AppError;
+use super::token_manager::TokenData;
+
+/// Environment variables checked for a token, in the same order `gh` uses.
+const TOKEN_ENV_VARS: &[&str] = &["GH_TOKEN", "GITHUB_TOKEN"];
+
+/// What GitHub reports about a token when it is used.
+#[derive(Debug, Clone)]
+pub struct TokenInfo {
+    pub username: String,
+    /// Scopes of a classic token; `None` for fine-grained tokens, which
+    /// don't advertise scopes.
+    pub scopes: Option<Vec<String>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<&TokenData> for TokenInfo {
+    fn from(token_data: &TokenData) -> Self {
+        Self {
+            username: token_data.login.clone(),
+            scopes: token_data.scopes.clone(),
+            expires_at: token_data.expires_at,
+        }
+    }
+}
+
+impl TokenInfo {
+    /// What the headers of an authenticated response say about the token
+    /// of user `username`.
+    pub(crate) fn from_headers(username: String, headers: &HeaderMap) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+        let scopes = header("x-oauth-scopes").map(|scopes| {
+            scopes
+                .split(',')
+                .map(|scope| scope.trim().to_string())
+                .filter(|scope| !scope.is_empty())
+                .collect()
+        });
+        let expires_at =
+            header("github-authentication-token-expiration").and_then(parse_expiration);
+
+        Self {
+            username,
+            scopes,
+            expires_at,
+        }
+    }
+
+    /// Scopes oxa requests that a classic token was not granted.
+    pub fn missing_scopes(&self) -> Vec<&'static str> {
+        let Some(scopes) = &self.scopes else {
+            return Vec::new();
+        };
+        config::get()
+            .auth
+            .scopes
+            .iter()
+            .map(String::as_str)
+            .filter(|wanted| !scopes.iter().any(|granted| granted == wanted))
+            .collect()
+    }
+
+    /// The token's scopes and expiry, e.g. `scopes: repo, workflow; no expiry`.
+    pub fn describe(&self) -> String {
+        let scopes = match &self.scopes {
+            Some(scopes) if scopes.is_empty() => "no scopes".to_string(),
+            Some(scopes) => format!("scopes: {}", scopes.join(", ")),
+            None => "fine-grained token".to_string(),
+        };
+        let expiry = match self.expires_at {
+            Some(expires_at) => format!("expires {}", expires_at.format("%Y-%m-%d")),
+            None => "no expiry".to_string(),
+        };
+        format!("{}; {}", scopes, expiry)
+    }
+}
+
+/// Return the first non-empty token found in the environment together with
+/// the name of the variable it came from.
+pub fn token_from_env() -> Option<(String, &'static str)> {
+    TOKEN_ENV_VARS.iter().find_map(|&var| {
+        std::env::var(var)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .map(|value| (value, var))
+    })
+}
+
+/// Read a token from `path`, ignoring surrounding whitespace.
+pub fn read_token_file(path: &Path) -> Result<String, AppError> {
+    let token = std::fs::read_to_string(path)?.trim().to_string();
+    if token.is_empty() {
+        return Err(AppError::AuthFailed(format!("{} is empty", path.display())));
+    }
+    Ok(token)
+}
+
+/// Check `access_token` against the API of `host` and report who it belongs
+/// to, its scopes and when it expires.
+pub async fn validate_token(host: &GitHubHost, access_token: &str) -> Result<TokenInfo, AppError> {
+    Ok(GitHubClient::validate(host, access_token).await?.1)
+}
+
+/// Validate a personal access token for `host` and turn it into token data
+/// ready to be stored.
+pub async fn personal_token_login(
+    host: &GitHubHost,
+    access_token: String,
+) -> Result<TokenData, AppError> {
+    let token_info = validate_token(host, &access_token).await?;
+
+    let mut token_data = TokenData::new(access_token, token_info.username, host);
+    token_data.scopes = token_info.scopes;
+    token_data.expires_at = token_info.expires_at;
+    Ok(token_data)
+}
+
+/// Parse GitHub's expiration header, e.g. `2024-03-15 06:13:01 UTC`.
+fn parse_expiration(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z")
+        .map(|time| time.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S UTC")
+                .ok()
+                .map(|time| time.and_utc())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[test]
+    fn reads_scopes_and_expiry_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-oauth-scopes", HeaderValue::from_static("repo, read:org,,workflow"));
+        headers.insert(
+            "github-authentication-token-expiration",
+            HeaderValue::from_static("2024-03-15 06:13:01 UTC"),
+        );
+        let token_info = TokenInfo::from_headers("octocat".to_string(), &headers);
+        assert_eq!(token_info.username, "octocat");
+        assert_eq!(
+            token_info.scopes,
+            Some(vec!["repo".to_string(), "read:org".to_string(), "workflow".to_string()]),
+        );
+        assert_eq!(
+            token_info.expires_at.map(|expires_at| expires_at.to_rfc3339()),
+            Some("2024-03-15T06:13:01+00:00".to_string()),
+        );
+    }
+
+    #[test]
+    fn fine_grained_tokens_have_no_scopes() {
+        let token_info = TokenInfo::from_headers("octocat".to_string(), &HeaderMap::new());
+        assert_eq!(token_info.scopes, None);
+        assert_eq!(token_info.expires_at, None);
+        assert_eq!(token_info.describe(), "fine-grained token; no expiry");
+    }
+
+    #[test]
+    fn parses_expiration_with_offset() {
+        let expires_at = parse_expiration("2024-03-15 08:13:01 +0200").unwrap();
+        assert_eq!(expires_at.to_rfc3339(), "2024-03-15T06:13:01+00:00");
+        assert_eq!(parse_expiration("next week"), None);
+    }
+}
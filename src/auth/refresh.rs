@@ -0,0 +1,100 @@
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+
+use crate::config::GitHubHost;
+use crate::utils::error::AppError;
+use super::auth_code::oauth_http_client;
+use super::token_manager::TokenData;
+
+/// Body returned by GitHub's token endpoint. GitHub answers errors with a
+/// 200 status and an `error` field, so both shapes share one struct.
+#[derive(Debug, Deserialize)]
+pub(super) struct TokenGrant {
+    pub access_token: Option<String>,
+    pub token_type: Option<String>,
+    pub scope: Option<String>,
+    pub expires_in: Option<i64>,
+    pub refresh_token: Option<String>,
+    pub refresh_token_expires_in: Option<i64>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+    pub interval: Option<u64>,
+}
+
+impl TokenGrant {
+    /// Turn a successful grant into stored token data for `login`.
+    pub fn into_token_data(
+        self,
+        access_token: String,
+        login: String,
+        host: &GitHubHost,
+    ) -> TokenData {
+        let now = Utc::now();
+        let mut token_data = TokenData::new(access_token, login, host);
+        if let Some(token_type) = self.token_type {
+            token_data.token_type = token_type;
+        }
+        // GitHub App user tokens send an empty scope string
+        token_data.scopes = self
+            .scope
+            .filter(|scope| !scope.is_empty())
+            .map(|scope| scope.split(',').map(|s| s.trim().to_string()).collect());
+        token_data.expires_at = self.expires_in.map(|secs| now + Duration::seconds(secs));
+        token_data.refresh_token = self.refresh_token;
+        token_data.refresh_token_expires_at = self
+            .refresh_token_expires_in
+            .map(|secs| now + Duration::seconds(secs));
+        token_data
+    }
+
+    /// The error GitHub reported, preferring its human-readable description.
+    pub fn error_message(&self) -> Option<String> {
+        self.error
+            .as_ref()
+            .map(|error| self.error_description.clone().unwrap_or_else(|| error.clone()))
+    }
+}
+
+/// Exchange the refresh token in `token_data` for a new access token.
+///
+/// Fails with [`AppError::AuthFailed`] when the refresh token itself has
+/// expired or was revoked, in which case the user has to log in again.
+pub async fn refresh_access_token(token_data: &TokenData) -> Result<TokenData, AppError> {
+    let Some(refresh_token) = &token_data.refresh_token else {
+        return Err(AppError::AuthFailed("token has no refresh token".to_string()));
+    };
+    if token_data
+        .refresh_token_expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(AppError::AuthFailed("refresh token has expired".to_string()));
+    }
+
+    let host = token_data.github_host();
+    let mut form = vec![
+        ("client_id", host.client_id()),
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+    ];
+    if !host.client_secret().is_empty() {
+        form.push(("client_secret", host.client_secret()));
+    }
+
+    let grant: TokenGrant = oauth_http_client()?
+        .post(host.token_url())
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&form)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    match grant.access_token.clone() {
+        Some(access_token) => {
+            Ok(grant.into_token_data(access_token, token_data.login.clone(), &host))
+        }
+        None => Err(AppError::AuthFailed(grant.error_message().unwrap_or_else(|| {
+            "token refresh returned no access token".to_string()
+        }))),
+    }
+}
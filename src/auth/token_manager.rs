@@ -1,51 +1,404 @@
-use anyhow::Result;
-use keyring::{Entry, Error};
+use chrono::{DateTime, Duration, Utc};
 use serde_json;
 
-use crate::config::{SERVICE_NAME, TOKEN_KEY};
+use crate::config::{ACCOUNTS_KEY, GITHUB_HOST, GitHubHost, TOKEN_KEY};
 use crate::utils::error::AppError;
+use super::credential_store::{CredentialStore, open_credential_store};
+use super::refresh::refresh_access_token;
+
+/// Refresh tokens this long before they expire so in-flight requests made
+/// with the old token still succeed.
+const REFRESH_MARGIN_MINUTES: i64 = 5;
 
 pub struct TokenManager {
-    entry: Entry,
+    store: Box<dyn CredentialStore>,
+}
+
+/// A stored account, identified by its login and host.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Account {
+    pub login: String,
+    pub host: String,
+}
+
+impl Account {
+    /// Stable identifier used as the credential store key suffix.
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.login, self.host)
+    }
+}
+
+/// Index of stored accounts, kept alongside the tokens themselves.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct AccountIndex {
+    active: Option<String>,
+    accounts: Vec<Account>,
 }
 
 impl TokenManager {
     pub fn new() -> Result<Self, AppError> {
-        let entry = Entry::new(SERVICE_NAME, TOKEN_KEY)?;
-        Ok(Self { entry })
+        Self::with_store(open_credential_store()?)
     }
 
-    pub fn store_token(&self, access_token: &str) -> Result<(), AppError> {
-        let token_data = TokenData {
-            access_token: access_token.to_string(),
-        };
+    fn with_store(store: Box<dyn CredentialStore>) -> Result<Self, AppError> {
+        let manager = Self { store };
+        manager.migrate_single_token()?;
+        Ok(manager)
+    }
+
+    /// Store `token_data` under its account and make that account active.
+    pub fn store_token(&self, token_data: &TokenData) -> Result<(), AppError> {
+        let account = token_data.account();
+        let json = serde_json::to_string(token_data)?;
+        self.store.set(&token_key(&account.id()), &json)?;
 
-        let json = serde_json::to_string(&token_data)?;
-        self.entry.set_password(&json)?;
-        Ok(())
+        let mut index = self.load_index()?;
+        if !index.accounts.contains(&account) {
+            index.accounts.push(account.clone());
+        }
+        index.active = Some(account.id());
+        self.save_index(&index)
     }
 
+    /// The token of the active account.
     pub fn get_token(&self) -> Result<Option<TokenData>, AppError> {
-        match self.entry.get_password() {
-            Ok(password) => {
-                let token_data: TokenData = serde_json::from_str(&password)?;
-                Ok(Some(token_data))
-            }
-            Err(Error::NoEntry) => Ok(None),
-            Err(e) => Err(AppError::KeyringError(e)),
+        let Some(active) = self.load_index()?.active else {
+            return Ok(None);
+        };
+        match self.store.get(&token_key(&active))? {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Like [`get_token`](Self::get_token), but first exchanges the refresh
+    /// token for a new access token when the stored one is about to expire.
+    pub async fn get_fresh_token(&self) -> Result<Option<TokenData>, AppError> {
+        let Some(token_data) = self.get_token()? else {
+            return Ok(None);
+        };
+        if !token_data.needs_refresh() {
+            return Ok(Some(token_data));
         }
+
+        let refreshed = refresh_access_token(&token_data).await?;
+        self.store_token(&refreshed)?;
+        Ok(Some(refreshed))
     }
 
+    /// Remove the active account's token. No account is active afterwards.
     pub fn clear_token(&self) -> Result<(), AppError> {
-        match self.entry.delete_password() {
-            Ok(_) => Ok(()),
-            Err(Error::NoEntry) => Ok(()), // Already cleared
-            Err(e) => Err(AppError::KeyringError(e)),
+        let mut index = self.load_index()?;
+        let Some(active) = index.active.take() else {
+            return Ok(());
+        };
+        self.store.delete(&token_key(&active))?;
+        index.accounts.retain(|account| account.id() != active);
+        self.save_index(&index)
+    }
+
+    /// All stored accounts, paired with whether each is the active one.
+    pub fn list_accounts(&self) -> Result<Vec<(Account, bool)>, AppError> {
+        let index = self.load_index()?;
+        Ok(index
+            .accounts
+            .into_iter()
+            .map(|account| {
+                let active = index.active.as_deref() == Some(account.id().as_str());
+                (account, active)
+            })
+            .collect())
+    }
+
+    /// Make the stored account `account_id` the active one.
+    pub fn set_active(&self, account_id: &str) -> Result<(), AppError> {
+        let mut index = self.load_index()?;
+        if !index.accounts.iter().any(|account| account.id() == account_id) {
+            return Err(AppError::CredentialStoreError(format!(
+                "no stored account {}",
+                account_id
+            )));
+        }
+        index.active = Some(account_id.to_string());
+        self.save_index(&index)
+    }
+
+    fn load_index(&self) -> Result<AccountIndex, AppError> {
+        match self.store.get(ACCOUNTS_KEY)? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(AccountIndex::default()),
+        }
+    }
+
+    fn save_index(&self, index: &AccountIndex) -> Result<(), AppError> {
+        self.store.set(ACCOUNTS_KEY, &serde_json::to_string(index)?)
+    }
+
+    /// Move a token stored by versions that kept a single credential under
+    /// `TOKEN_KEY` into the account index.
+    fn migrate_single_token(&self) -> Result<(), AppError> {
+        let Some(json) = self.store.get(TOKEN_KEY)? else {
+            return Ok(());
+        };
+        let token_data: TokenData = serde_json::from_str(&json)?;
+        // Records that predate the login field can't be keyed; drop them
+        if !token_data.login.is_empty() {
+            self.store_token(&token_data)?;
         }
+        self.store.delete(TOKEN_KEY)
     }
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+fn token_key(account_id: &str) -> String {
+    format!("{}:{}", TOKEN_KEY, account_id)
+}
+
+/// A stored credential. Fields other than `access_token` are defaulted so
+/// records written by older versions still load.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TokenData {
     pub access_token: String,
+    #[serde(default = "default_token_type")]
+    pub token_type: String,
+    /// Granted scopes, or `None` when the token doesn't advertise any
+    /// (fine-grained personal access tokens and GitHub App user tokens).
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub refresh_token_expires_at: Option<DateTime<Utc>>,
+    /// Web host of the instance the token belongs to.
+    #[serde(default = "default_host")]
+    pub host: String,
+    /// API base when it differs from the conventional one for `host`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base: Option<String>,
+    /// OAuth app the token was issued to, when not the built-in one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub login: String,
+}
+
+impl TokenData {
+    /// A non-expiring bearer token for `login` on `host`.
+    pub fn new(access_token: String, login: String, host: &GitHubHost) -> Self {
+        let conventional = GitHubHost::new(&host.web_host, None);
+        Self {
+            access_token,
+            token_type: default_token_type(),
+            scopes: None,
+            created_at: Utc::now(),
+            expires_at: None,
+            refresh_token: None,
+            refresh_token_expires_at: None,
+            host: host.web_host.clone(),
+            api_base: (host.api_base != conventional.api_base).then(|| host.api_base.clone()),
+            client_id: host.client_id.clone(),
+            login,
+        }
+    }
+
+    /// Endpoints of the instance this token belongs to.
+    pub fn github_host(&self) -> GitHubHost {
+        let mut host = GitHubHost::new(&self.host, self.api_base.as_deref());
+        host.client_id = self.client_id.clone();
+        host
+    }
+
+    pub fn account(&self) -> Account {
+        Account {
+            login: self.login.clone(),
+            host: self.host.clone(),
+        }
+    }
+
+    /// Whether the access token expires soon and can be refreshed.
+    pub fn needs_refresh(&self) -> bool {
+        let Some(expires_at) = self.expires_at else {
+            return false;
+        };
+        self.refresh_token.is_some()
+            && expires_at - Duration::minutes(REFRESH_MARGIN_MINUTES) <= Utc::now()
+    }
+}
+
+fn default_token_type() -> String {
+    "bearer".to_string()
+}
+
+fn default_host() -> String {
+    GITHUB_HOST.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    /// A credential store in memory, shared between clones so tests can look
+    /// inside after handing one to a `TokenManager`.
+    #[derive(Clone, Default)]
+    struct MemoryStore(Arc<Mutex<BTreeMap<String, String>>>);
+
+    impl MemoryStore {
+        fn keys(&self) -> Vec<String> {
+            self.0.lock().unwrap().keys().cloned().collect()
+        }
+    }
+
+    impl CredentialStore for MemoryStore {
+        fn get(&self, key: &str) -> Result<Option<String>, AppError> {
+            Ok(self.0.lock().unwrap().get(key).cloned())
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), AppError> {
+            self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, key: &str) -> Result<(), AppError> {
+            self.0.lock().unwrap().remove(key);
+            Ok(())
+        }
+    }
+
+    fn manager(store: &MemoryStore) -> TokenManager {
+        TokenManager::with_store(Box::new(store.clone())).unwrap()
+    }
+
+    fn token(login: &str, host: &str) -> TokenData {
+        let host = GitHubHost::new(host, None);
+        TokenData::new(format!("token-of-{}", login), login.to_string(), &host)
+    }
+
+    fn accounts(manager: &TokenManager) -> Vec<(String, bool)> {
+        manager
+            .list_accounts()
+            .unwrap()
+            .into_iter()
+            .map(|(account, active)| (account.id(), active))
+            .collect()
+    }
+
+    fn active_token(manager: &TokenManager) -> Option<String> {
+        manager.get_token().unwrap().map(|token_data| token_data.access_token)
+    }
+
+    #[test]
+    fn empty_store_has_no_accounts() {
+        let manager = manager(&MemoryStore::default());
+        assert!(accounts(&manager).is_empty());
+        assert_eq!(active_token(&manager), None);
+        manager.clear_token().unwrap();
+    }
+
+    #[test]
+    fn adding_an_account_makes_it_active() {
+        let store = MemoryStore::default();
+        let manager = manager(&store);
+        manager.store_token(&token("octocat", "github.com")).unwrap();
+        manager.store_token(&token("octocat", "ghe.example.com")).unwrap();
+
+        assert_eq!(
+            accounts(&manager),
+            [
+                ("octocat@github.com".to_string(), false),
+                ("octocat@ghe.example.com".to_string(), true),
+            ],
+        );
+        assert_eq!(
+            store.keys(),
+            [
+                ACCOUNTS_KEY.to_string(),
+                format!("{}:octocat@ghe.example.com", TOKEN_KEY),
+                format!("{}:octocat@github.com", TOKEN_KEY),
+            ],
+        );
+
+        // Storing a new token for a known account replaces it in place
+        let mut renewed = token("octocat", "github.com");
+        renewed.access_token = "renewed".to_string();
+        manager.store_token(&renewed).unwrap();
+        assert_eq!(accounts(&manager).len(), 2);
+        assert_eq!(active_token(&manager).as_deref(), Some("renewed"));
+    }
+
+    #[test]
+    fn switches_between_accounts() {
+        let manager = manager(&MemoryStore::default());
+        manager.store_token(&token("alice", "github.com")).unwrap();
+        manager.store_token(&token("bob", "github.com")).unwrap();
+        assert_eq!(active_token(&manager).as_deref(), Some("token-of-bob"));
+
+        manager.set_active("alice@github.com").unwrap();
+        assert_eq!(active_token(&manager).as_deref(), Some("token-of-alice"));
+        assert_eq!(
+            accounts(&manager),
+            [("alice@github.com".to_string(), true), ("bob@github.com".to_string(), false)],
+        );
+
+        assert!(manager.set_active("carol@github.com").is_err());
+        assert_eq!(active_token(&manager).as_deref(), Some("token-of-alice"));
+    }
+
+    #[test]
+    fn removing_the_active_account_leaves_none_active() {
+        let store = MemoryStore::default();
+        let manager = manager(&store);
+        manager.store_token(&token("alice", "github.com")).unwrap();
+        manager.store_token(&token("bob", "github.com")).unwrap();
+
+        manager.clear_token().unwrap();
+        assert_eq!(accounts(&manager), [("alice@github.com".to_string(), false)]);
+        assert_eq!(active_token(&manager), None);
+        assert!(!store.keys().contains(&format!("{}:bob@github.com", TOKEN_KEY)));
+
+        // The remaining account can still be switched to
+        manager.set_active("alice@github.com").unwrap();
+        assert_eq!(active_token(&manager).as_deref(), Some("token-of-alice"));
+    }
+
+    #[test]
+    fn migrates_legacy_single_token() {
+        let store = MemoryStore::default();
+        store
+            .set(TOKEN_KEY, r#"{"access_token":"legacy","login":"octocat"}"#)
+            .unwrap();
+
+        let manager = manager(&store);
+        assert_eq!(accounts(&manager), [("octocat@github.com".to_string(), true)]);
+        assert_eq!(active_token(&manager).as_deref(), Some("legacy"));
+        assert!(!store.keys().contains(&TOKEN_KEY.to_string()));
+    }
+
+    #[test]
+    fn drops_legacy_token_without_login() {
+        let store = MemoryStore::default();
+        store.set(TOKEN_KEY, r#"{"access_token":"legacy"}"#).unwrap();
+
+        let manager = manager(&store);
+        assert!(accounts(&manager).is_empty());
+        assert!(store.keys().is_empty());
+    }
+
+    #[test]
+    fn refreshes_only_expiring_refreshable_tokens() {
+        let mut token_data = token("octocat", "github.com");
+        assert!(!token_data.needs_refresh());
+
+        token_data.expires_at = Some(Utc::now() + Duration::minutes(1));
+        assert!(!token_data.needs_refresh());
+        token_data.refresh_token = Some("refresh".to_string());
+        assert!(token_data.needs_refresh());
+
+        token_data.expires_at = Some(Utc::now() + Duration::hours(1));
+        assert!(!token_data.needs_refresh());
+    }
 }
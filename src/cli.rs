@@ -0,0 +1,166 @@
+use clap::{Args, Parser, Subcommand};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::auth::{read_token_file, token_from_env};
+use crate::commands::output::{OutputFormat, Selection};
+use crate::config::{CredentialStoreKind, ThemeName};
+use crate::utils::error::AppError;
+
+/// Command line arguments. Flags backed by an environment variable override
+/// the config file, which overrides the built-in defaults.
+#[derive(Debug, Parser)]
+#[command(name = "oxa", version, about = "Browse GitHub Actions runs from the terminal")]
+pub struct Cli {
+    /// What to do; the TUI when omitted.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Config file to use instead of $XDG_CONFIG_HOME/oxa/config.toml.
+    #[arg(long, global = true, env = "OXA_CONFIG", value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Use a personal access token read from this file. Without it,
+    /// GH_TOKEN or GITHUB_TOKEN are used when set.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub token_file: Option<PathBuf>,
+
+    /// GitHub host to log in to, e.g. a GitHub Enterprise Server instance.
+    #[arg(long, global = true, env = "GH_HOST", value_name = "HOST")]
+    pub hostname: Option<String>,
+
+    /// REST API base URL, when it isn't the usual one for the host
+    /// (https://HOST/api/v3 on GitHub Enterprise Server).
+    #[arg(long, global = true, env = "OXA_API_URL", value_name = "URL")]
+    pub api_url: Option<String>,
+
+    /// Client ID of the OAuth app registered on the host.
+    #[arg(long, global = true, env = "OXA_CLIENT_ID", value_name = "ID")]
+    pub client_id: Option<String>,
+
+    /// Secret of the OAuth app given by --client-id, if it has one.
+    #[arg(
+        long,
+        global = true,
+        env = "OXA_CLIENT_SECRET",
+        value_name = "SECRET",
+        hide_env_values = true
+    )]
+    pub client_secret: Option<String>,
+
+    /// Where to keep credentials.
+    #[arg(long, global = true, env = "OXA_CREDENTIAL_STORE", value_name = "STORE")]
+    pub credential_store: Option<CredentialStoreKind>,
+
+    /// Colour scheme.
+    #[arg(long, global = true, env = "OXA_THEME", value_name = "THEME")]
+    pub theme: Option<ThemeName>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Open the terminal UI (the default).
+    Tui,
+    /// Log in and store the credentials. Uses the device code flow unless
+    /// told otherwise.
+    Login {
+        /// Log in through the browser with a local callback instead.
+        #[arg(long, conflicts_with = "with_token")]
+        web: bool,
+        /// Read a personal access token from standard input.
+        #[arg(long)]
+        with_token: bool,
+    },
+    /// Remove the stored credentials of the active account.
+    Logout,
+    /// Show the stored accounts and check the active token.
+    Status,
+    /// List the repositories you have access to.
+    Repos {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// List recent workflow runs of a repository, newest first.
+    Runs {
+        /// Repository as OWNER/REPO.
+        repo: RepoArg,
+        /// Page of results to show.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        page: u32,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Follow a workflow run until it completes. Exits non-zero unless the
+    /// run succeeds.
+    Watch {
+        /// ID of the run, as listed by `oxa runs`.
+        run_id: u64,
+        /// Repository the run belongs to, as OWNER/REPO.
+        #[arg(short = 'R', long)]
+        repo: RepoArg,
+        /// Seconds between polls; the configured active refresh interval
+        /// when omitted.
+        #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: Option<u64>,
+    },
+    /// Check workflow files for structural mistakes, offline. Exits
+    /// non-zero when any are found.
+    Lint {
+        /// A workflow file, or a directory whose .yml and .yaml files are
+        /// checked.
+        #[arg(default_value = ".github/workflows")]
+        path: PathBuf,
+    },
+}
+
+/// How listing commands print their results.
+#[derive(Debug, Args)]
+pub struct OutputArgs {
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+    pub format: OutputFormat,
+    /// Fields to print, as jq-style paths separated by commas, e.g.
+    /// `.name,.last_run.conclusion`. A single path prints bare values.
+    #[arg(long, value_name = "PATHS")]
+    pub jq: Option<Selection>,
+}
+
+impl Cli {
+    /// A token given with `--token-file` or through the environment.
+    pub fn supplied_token(&self) -> Result<Option<String>, AppError> {
+        if let Some(path) = &self.token_file {
+            return read_token_file(path).map(Some);
+        }
+        Ok(token_from_env().map(|(token, _)| token))
+    }
+}
+
+/// A repository given as `OWNER/REPO`.
+#[derive(Debug, Clone)]
+pub struct RepoArg {
+    pub owner: String,
+    pub name: String,
+}
+
+impl FromStr for RepoArg {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+                Ok(Self {
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                })
+            }
+            _ => Err(format!("expected OWNER/REPO, got '{}'", value)),
+        }
+    }
+}
+
+impl fmt::Display for RepoArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.name)
+    }
+}
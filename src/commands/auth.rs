@@ -0,0 +1,115 @@
+use std::io::Read;
+
+use crate::auth::{
+    DevicePoll, TokenData, TokenManager, finish_auth_code_flow, personal_token_login,
+    poll_device_token, request_device_code, start_auth_code_flow, validate_token,
+};
+use crate::auth::credential_store::StoreProtection;
+use crate::cli::Cli;
+use crate::config::{self, GitHubHost};
+use crate::utils::error::AppError;
+use super::{EXIT_AUTH, EXIT_OK, not_logged_in};
+
+/// `oxa login`: run one of the login flows and store the resulting token.
+pub async fn login(web: bool, with_token: bool) -> Result<i32, AppError> {
+    let host = config::get().login_host();
+
+    let token_data = if with_token {
+        let mut token = String::new();
+        std::io::stdin().read_to_string(&mut token)?;
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            return Err(AppError::AuthFailed("no token on standard input".to_string()));
+        }
+        personal_token_login(&host, token).await?
+    } else if web {
+        eprintln!("Opening {} in your browser...", host.web_host);
+        let login = start_auth_code_flow(&host)?;
+        if let Some(error) = &login.browser_error {
+            eprintln!("Could not open a browser ({}); open this URL:", error);
+            eprintln!("{}", login.authorize_url);
+        }
+        finish_auth_code_flow(login).await?
+    } else {
+        device_code_login(&host).await?
+    };
+
+    TokenManager::new()?.store_token(&token_data)?;
+    eprintln!("Logged in to {} as {}", token_data.host, token_data.login);
+    Ok(EXIT_OK)
+}
+
+/// Print the verification URI and user code, then poll until the user has
+/// approved the device on another screen.
+async fn device_code_login(host: &GitHubHost) -> Result<TokenData, AppError> {
+    let mut authorization = request_device_code(host).await?;
+    eprintln!(
+        "Open {} and enter the code {}",
+        authorization.verification_uri, authorization.user_code
+    );
+
+    loop {
+        tokio::time::sleep(authorization.interval).await;
+        match poll_device_token(&mut authorization).await? {
+            DevicePoll::Pending | DevicePoll::SlowDown => {}
+            DevicePoll::Complete(token_data) => return Ok(*token_data),
+        }
+    }
+}
+
+/// `oxa logout`: forget the active account.
+pub fn logout() -> Result<i32, AppError> {
+    let token_manager = TokenManager::new()?;
+    let Some(token_data) = token_manager.get_token()? else {
+        return Err(not_logged_in());
+    };
+    token_manager.clear_token()?;
+    eprintln!("Logged out of {} as {}", token_data.host, token_data.login);
+    Ok(EXIT_OK)
+}
+
+/// `oxa status`: list the stored accounts and check that the token in use is
+/// still accepted.
+pub async fn status(cli: &Cli) -> Result<i32, AppError> {
+    if let Some(token) = cli.supplied_token()? {
+        let host = config::get().login_host();
+        let token_info = validate_token(&host, &token).await?;
+        println!("{} as {} (supplied token)", host.web_host, token_info.username);
+        println!("  {}", token_info.describe());
+        return Ok(EXIT_OK);
+    }
+
+    let token_manager = TokenManager::new()?;
+    let accounts = token_manager.list_accounts()?;
+    if accounts.is_empty() {
+        eprintln!("Not logged in to any host; run `oxa login`");
+        return Ok(EXIT_AUTH);
+    }
+    for (account, active) in &accounts {
+        let marker = if *active { "*" } else { " " };
+        println!("{} {} as {}", marker, account.host, account.login);
+    }
+    let protection = StoreProtection::current();
+    println!("  credentials: {}", protection.describe());
+    if protection == StoreProtection::MachineBound {
+        eprintln!(
+            "Warning: stored tokens are not encrypted against other users of this machine"
+        );
+    }
+
+    let Some(token_data) = token_manager.get_fresh_token().await? else {
+        eprintln!("No active account; run `oxa login`");
+        return Ok(EXIT_AUTH);
+    };
+    let mut token_info =
+        validate_token(&token_data.github_host(), &token_data.access_token).await?;
+    // Expiry of OAuth tokens is only known from the token grant
+    token_info.expires_at = token_info.expires_at.or(token_data.expires_at);
+    println!("  {}", token_info.describe());
+
+    let missing = token_info.missing_scopes();
+    if !missing.is_empty() {
+        println!("  missing scopes: {}", missing.join(", "));
+    }
+    Ok(EXIT_OK)
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::utils::error::AppError;
+use crate::workflow::lint;
+use super::{EXIT_ERROR, EXIT_OK};
+
+/// `oxa lint`: check workflow files without contacting GitHub. Each problem
+/// is printed as `FILE:LINE:COL: message`; the exit code says whether
+/// there were any.
+pub fn lint(path: &Path) -> Result<i32, AppError> {
+    let files = workflow_files(path)?;
+    if files.is_empty() {
+        return Err(AppError::ConfigError(format!(
+            "no workflow files (*.yml, *.yaml) in {}",
+            path.display()
+        )));
+    }
+
+    let mut problems = 0;
+    let mut failing = 0;
+    for file in &files {
+        let source = fs::read_to_string(file).map_err(|e| with_path(file, e))?;
+        let diagnostics = lint::lint(&source);
+        for diagnostic in &diagnostics {
+            println!(
+                "{}:{}:{}: {}",
+                file.display(),
+                diagnostic.mark.line,
+                diagnostic.mark.col,
+                diagnostic.message
+            );
+        }
+        if !diagnostics.is_empty() {
+            problems += diagnostics.len();
+            failing += 1;
+        }
+    }
+
+    if problems == 0 {
+        eprintln!("No problems in {}", count(files.len(), "workflow file"));
+        return Ok(EXIT_OK);
+    }
+    eprintln!(
+        "{} in {} of {}",
+        count(problems, "problem"),
+        failing,
+        count(files.len(), "workflow file")
+    );
+    Ok(EXIT_ERROR)
+}
+
+/// `path` itself, or the YAML files directly in it when it is a directory,
+/// which is where GitHub looks for workflows.
+fn workflow_files(path: &Path) -> Result<Vec<PathBuf>, AppError> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path).map_err(|e| with_path(path, e))? {
+        let file = entry.map_err(|e| with_path(path, e))?.path();
+        let is_yaml = matches!(
+            file.extension().and_then(|extension| extension.to_str()),
+            Some("yml" | "yaml")
+        );
+        if is_yaml && file.is_file() {
+            files.push(file);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn with_path(path: &Path, error: io::Error) -> AppError {
+    AppError::IoError(io::Error::new(
+        error.kind(),
+        format!("{}: {}", path.display(), error),
+    ))
+}
+
+fn count(n: usize, noun: &str) -> String {
+    format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+}
@@ -0,0 +1,67 @@
+mod auth;
+mod lint;
+pub mod output;
+mod runs;
+
+use crate::auth::TokenManager;
+use crate::cli::{Cli, Command};
+use crate::config;
+use crate::github::GitHubClient;
+use crate::utils::error::AppError;
+
+// Exit codes, following the GitHub CLI where it has an equivalent
+pub const EXIT_OK: i32 = 0;
+/// Any failure, including a watched run that didn't succeed.
+pub const EXIT_ERROR: i32 = 1;
+/// Invalid arguments or configuration.
+pub const EXIT_USAGE: i32 = 2;
+/// Not logged in, or the stored credentials were rejected.
+pub const EXIT_AUTH: i32 = 4;
+
+/// Run a non-interactive subcommand and return the process exit code.
+/// Results go to stdout, progress and errors to stderr.
+pub async fn run(cli: &Cli, command: &Command) -> i32 {
+    let result = match command {
+        Command::Tui => unreachable!("the TUI is started by main"),
+        Command::Login { web, with_token } => auth::login(*web, *with_token).await,
+        Command::Logout => auth::logout(),
+        Command::Status => auth::status(cli).await,
+        Command::Repos { output } => runs::repos(cli, output).await,
+        Command::Runs { repo, page, output } => runs::runs(cli, repo, *page, output).await,
+        Command::Watch {
+            run_id,
+            repo,
+            interval,
+        } => runs::watch(cli, repo, *run_id, *interval).await,
+        Command::Lint { path } => lint::lint(path),
+    };
+
+    result.unwrap_or_else(|e| {
+        eprintln!("oxa: {}", e);
+        exit_code(&e)
+    })
+}
+
+fn exit_code(error: &AppError) -> i32 {
+    match error {
+        AppError::AuthFailed(_) | AppError::AuthTimeout => EXIT_AUTH,
+        AppError::ConfigError(_) => EXIT_USAGE,
+        _ => EXIT_ERROR,
+    }
+}
+
+/// A client for the token given on the command line or in the environment,
+/// or else for the active stored account.
+async fn client(cli: &Cli) -> Result<GitHubClient, AppError> {
+    if let Some(token) = cli.supplied_token()? {
+        return GitHubClient::new(&config::get().login_host(), &token).await;
+    }
+    match TokenManager::new()?.get_fresh_token().await? {
+        Some(token_data) => GitHubClient::from_token(&token_data),
+        None => Err(not_logged_in()),
+    }
+}
+
+fn not_logged_in() -> AppError {
+    AppError::AuthFailed("not logged in; run `oxa login` first".to_string())
+}
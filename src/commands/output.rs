@@ -0,0 +1,291 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::cli::OutputArgs;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns with a header row.
+    Table,
+    /// A single JSON array.
+    Json,
+    /// One JSON value per line.
+    Ndjson,
+    /// Comma-separated values with a header row.
+    Csv,
+}
+
+/// Fields picked with `--jq`, as paths like `.last_run.conclusion`.
+#[derive(Debug, Clone)]
+pub struct Selection {
+    paths: Vec<FieldPath>,
+}
+
+#[derive(Debug, Clone)]
+struct FieldPath {
+    /// The path as written, without the leading dot; used as the key or
+    /// column header.
+    name: String,
+    segments: Vec<String>,
+}
+
+impl FromStr for Selection {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let paths = value
+            .split(',')
+            .map(|path| {
+                let path = path.trim();
+                let rest = path
+                    .strip_prefix('.')
+                    .ok_or_else(|| format!("'{}' is not a path; paths start with '.'", path))?;
+                let segments: Vec<String> = rest.split('.').map(str::to_string).collect();
+                if segments.iter().any(String::is_empty) {
+                    return Err(format!("'{}' has an empty field name", path));
+                }
+                Ok(FieldPath {
+                    name: rest.to_string(),
+                    segments,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { paths })
+    }
+}
+
+impl FieldPath {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            segments: name.split('.').map(str::to_string).collect(),
+        }
+    }
+
+    /// The value at this path, `null` when any part of it is missing.
+    /// Numeric segments index into arrays.
+    fn get<'a>(&self, value: &'a Value) -> &'a Value {
+        self.segments
+            .iter()
+            .try_fold(value, |value, segment| match value {
+                Value::Object(map) => map.get(segment),
+                Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => None,
+            })
+            .unwrap_or(&Value::Null)
+    }
+}
+
+/// Print `records` in the format requested on the command line. `columns`
+/// are the fields the table and CSV formats show when `--jq` picks none.
+pub fn print_records<T: Serialize>(
+    records: &[T],
+    columns: &[&str],
+    output: &OutputArgs,
+) -> Result<(), AppError> {
+    let records = records
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stdout = io::stdout().lock();
+    match output.format {
+        OutputFormat::Json => {
+            let values: Vec<Value> = records
+                .iter()
+                .map(|record| select(record, output.jq.as_ref()))
+                .collect();
+            serde_json::to_writer_pretty(&mut stdout, &values)?;
+            writeln!(stdout)?;
+        }
+        OutputFormat::Ndjson => {
+            for record in &records {
+                serde_json::to_writer(&mut stdout, &select(record, output.jq.as_ref()))?;
+                writeln!(stdout)?;
+            }
+        }
+        OutputFormat::Table | OutputFormat::Csv => {
+            let paths = match &output.jq {
+                Some(selection) => selection.paths.clone(),
+                None => columns.iter().map(|name| FieldPath::new(name)).collect(),
+            };
+            let rows: Vec<Vec<String>> = records
+                .iter()
+                .map(|record| paths.iter().map(|path| cell(path.get(record))).collect())
+                .collect();
+            let header: Vec<String> = paths.iter().map(|path| path.name.clone()).collect();
+
+            if output.format == OutputFormat::Csv {
+                write_csv(&mut stdout, &header, &rows)?;
+            } else {
+                let header = header.iter().map(|name| name.to_uppercase()).collect();
+                write_table(&mut stdout, &header, &rows)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply `--jq` to a record: the whole record without a selection, the bare
+/// value for a single path, otherwise an object keyed by path.
+fn select(record: &Value, selection: Option<&Selection>) -> Value {
+    match selection.map(|selection| selection.paths.as_slice()) {
+        None => record.clone(),
+        Some([path]) => path.get(record).clone(),
+        Some(paths) => Value::Object(
+            paths
+                .iter()
+                .map(|path| (path.name.clone(), path.get(record).clone()))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+/// Render a value for a table or CSV cell: strings unquoted, null empty and
+/// nested values as compact JSON.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn write_table(out: &mut impl Write, header: &Vec<String>, rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|name| name.chars().count()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    for row in std::iter::once(header).chain(rows) {
+        let last = row.len().saturating_sub(1);
+        for (i, (value, width)) in row.iter().zip(&widths).enumerate() {
+            if i == last {
+                writeln!(out, "{}", value)?;
+            } else {
+                write!(out, "{:<width$}  ", value, width = width)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_csv(out: &mut impl Write, header: &[String], rows: &[Vec<String>]) -> io::Result<()> {
+    for row in std::iter::once(header).chain(rows.iter().map(Vec::as_slice)) {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record() -> Value {
+        json!({
+            "name": "CI",
+            "last_run": { "conclusion": "success", "actor": { "login": "octocat" } },
+            "jobs": [{ "name": "build" }, { "name": "test" }],
+        })
+    }
+
+    fn selection(paths: &str) -> Selection {
+        paths.parse().unwrap()
+    }
+
+    fn csv(header: &[&str], rows: &[&[&str]]) -> String {
+        let header: Vec<String> = header.iter().map(|s| s.to_string()).collect();
+        let rows: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| row.iter().map(|s| s.to_string()).collect())
+            .collect();
+        let mut out = Vec::new();
+        write_csv(&mut out, &header, &rows).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn selects_nested_path() {
+        let record = record();
+        let selection = selection(".last_run.actor.login");
+        assert_eq!(select(&record, Some(&selection)), json!("octocat"));
+    }
+
+    #[test]
+    fn selects_array_index() {
+        let record = record();
+        assert_eq!(select(&record, Some(&selection(".jobs.1.name"))), json!("test"));
+        assert_eq!(select(&record, Some(&selection(".jobs.5.name"))), Value::Null);
+        assert_eq!(select(&record, Some(&selection(".jobs.first"))), Value::Null);
+    }
+
+    #[test]
+    fn missing_fields_are_null() {
+        let record = record();
+        assert_eq!(select(&record, Some(&selection(".missing"))), Value::Null);
+        // Descending into a string finds nothing either
+        assert_eq!(select(&record, Some(&selection(".name.length"))), Value::Null);
+        assert_eq!(cell(&Value::Null), "");
+    }
+
+    #[test]
+    fn several_paths_are_keyed_by_path() {
+        let record = record();
+        let selection = selection(".name, .last_run.conclusion, .nope");
+        assert_eq!(
+            select(&record, Some(&selection)),
+            json!({ "name": "CI", "last_run.conclusion": "success", "nope": null }),
+        );
+        assert_eq!(select(&record, None), record);
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        assert!("name".parse::<Selection>().is_err());
+        assert!(".last_run..conclusion".parse::<Selection>().is_err());
+        assert!(".name,".parse::<Selection>().is_err());
+    }
+
+    #[test]
+    fn cells_render_nested_values_as_json() {
+        let record = record();
+        assert_eq!(cell(FieldPath::new("name").get(&record)), "CI");
+        assert_eq!(cell(FieldPath::new("jobs.0").get(&record)), r#"{"name":"build"}"#);
+        assert_eq!(cell(&json!(3)), "3");
+    }
+
+    #[test]
+    fn csv_quotes_special_cells() {
+        let out = csv(
+            &["name", "title"],
+            &[
+                &["plain", "a, b"],
+                &["say \"hi\"", "line one\nline two"],
+                &["cr\r", ""],
+            ],
+        );
+        assert_eq!(
+            out,
+            "name,title\n\
+             plain,\"a, b\"\n\
+             \"say \"\"hi\"\"\",\"line one\nline two\"\n\
+             \"cr\r\",\n",
+        );
+    }
+}
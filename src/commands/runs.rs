@@ -0,0 +1,89 @@
+use chrono::Utc;
+use std::time::Duration;
+
+use crate::cli::{Cli, OutputArgs, RepoArg};
+use crate::config;
+use crate::utils::error::AppError;
+use crate::utils::format::format_wait;
+use super::output::print_records;
+use super::{EXIT_ERROR, EXIT_OK, client};
+
+/// Conclusions that count as a successful run for `oxa watch`.
+const PASSING_CONCLUSIONS: &[&str] = &["success", "neutral", "skipped"];
+
+/// Fields shown by the table and CSV formats unless `--jq` picks others.
+const REPO_COLUMNS: &[&str] = &["owner", "repo_name", "visibility", "fork", "archived"];
+const RUN_COLUMNS: &[&str] = &[
+    "id",
+    "status",
+    "conclusion",
+    "run_number",
+    "name",
+    "head_branch",
+    "event",
+    "updated_at",
+];
+
+/// `oxa repos`: the repositories the user can access.
+pub async fn repos(cli: &Cli, output: &OutputArgs) -> Result<i32, AppError> {
+    let repos = client(cli).await?.get_user_repos().await?;
+    print_records(&repos, REPO_COLUMNS, output)?;
+    Ok(EXIT_OK)
+}
+
+/// `oxa runs`: one page of a repository's workflow runs.
+pub async fn runs(
+    cli: &Cli,
+    repo: &RepoArg,
+    page: u32,
+    output: &OutputArgs,
+) -> Result<i32, AppError> {
+    let run_page = client(cli)
+        .await?
+        .get_repo_workflow_runs(&repo.owner, &repo.name, None, page)
+        .await?;
+    print_records(&run_page.runs, RUN_COLUMNS, output)?;
+    Ok(EXIT_OK)
+}
+
+/// `oxa watch`: poll a run, reporting each status change, until it
+/// completes. The exit code reflects the run's conclusion.
+pub async fn watch(
+    cli: &Cli,
+    repo: &RepoArg,
+    run_id: u64,
+    interval: Option<u64>,
+) -> Result<i32, AppError> {
+    let client = client(cli).await?;
+    let interval = Duration::from_secs(
+        interval.unwrap_or_else(|| config::get().refresh.active_secs.get()),
+    );
+
+    let mut last_status = None;
+    loop {
+        let run = match client.get_workflow_run(&repo.owner, &repo.name, run_id).await {
+            Ok(run) => run,
+            // Keep watching once the limit resets rather than giving up
+            Err(AppError::RateLimited(until)) => {
+                eprintln!("Rate limited; resuming in {}", format_wait(until));
+                tokio::time::sleep((until - Utc::now()).to_std().unwrap_or(interval)).await;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if last_status.as_deref() != Some(run.status.as_str()) {
+            eprintln!("{} #{} {}: {}", repo, run.run_number, run.name, run.status);
+            last_status = Some(run.status.clone());
+        }
+
+        if run.is_completed() {
+            println!("{}", run.outcome());
+            return Ok(if PASSING_CONCLUSIONS.contains(&run.outcome()) {
+                EXIT_OK
+            } else {
+                EXIT_ERROR
+            });
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -0,0 +1,259 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use std::fmt;
+
+/// Something the user can do from the main panels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    NextPanel,
+    Logs,
+    WorkflowFile,
+    Rerun,
+    RerunFailed,
+    Cancel,
+    ForceCancel,
+    Dispatch,
+    ToggleWorkflow,
+    Login,
+    Accounts,
+    Quit,
+}
+
+impl Action {
+    /// The key of the action in the `[keybindings]` table.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::NextPanel => "next_panel",
+            Action::Logs => "logs",
+            Action::WorkflowFile => "workflow_file",
+            Action::Rerun => "rerun",
+            Action::RerunFailed => "rerun_failed",
+            Action::Cancel => "cancel",
+            Action::ForceCancel => "force_cancel",
+            Action::Dispatch => "dispatch",
+            Action::ToggleWorkflow => "toggle_workflow",
+            Action::Login => "login",
+            Action::Accounts => "accounts",
+            Action::Quit => "quit",
+        }
+    }
+}
+
+/// The `[keybindings]` table: the keys that trigger each action. Popups
+/// keep their fixed keys.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    up: Vec<KeySpec>,
+    down: Vec<KeySpec>,
+    left: Vec<KeySpec>,
+    right: Vec<KeySpec>,
+    next_panel: Vec<KeySpec>,
+    logs: Vec<KeySpec>,
+    workflow_file: Vec<KeySpec>,
+    rerun: Vec<KeySpec>,
+    rerun_failed: Vec<KeySpec>,
+    cancel: Vec<KeySpec>,
+    force_cancel: Vec<KeySpec>,
+    dispatch: Vec<KeySpec>,
+    toggle_workflow: Vec<KeySpec>,
+    login: Vec<KeySpec>,
+    accounts: Vec<KeySpec>,
+    quit: Vec<KeySpec>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let keys = |specs: &[&str]| {
+            specs
+                .iter()
+                .map(|spec| KeySpec::parse(spec).expect("valid default key"))
+                .collect()
+        };
+        Self {
+            up: keys(&["Up", "k"]),
+            down: keys(&["Down", "j"]),
+            left: keys(&["Left", "h"]),
+            right: keys(&["Right"]),
+            next_panel: keys(&["Tab"]),
+            logs: keys(&["Enter"]),
+            workflow_file: keys(&["w"]),
+            rerun: keys(&["r"]),
+            rerun_failed: keys(&["f"]),
+            cancel: keys(&["c"]),
+            force_cancel: keys(&["C"]),
+            dispatch: keys(&["d"]),
+            toggle_workflow: keys(&["e"]),
+            login: keys(&["L", "l"]),
+            accounts: keys(&["A", "a"]),
+            quit: keys(&["Q", "q"]),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// The action bound to `key`, if any.
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        self.bindings()
+            .into_iter()
+            .find(|(_, keys)| keys.iter().any(|spec| spec.matches(key)))
+            .map(|(action, _)| action)
+    }
+
+    /// The first key bound to `action`, for help texts.
+    pub fn label(&self, action: Action) -> String {
+        self.bindings()
+            .into_iter()
+            .find(|(bound, _)| *bound == action)
+            .and_then(|(_, keys)| keys.first())
+            .map_or_else(|| "-".to_string(), |spec| spec.to_string())
+    }
+
+    /// The first action whose key is already bound to an earlier action.
+    pub(super) fn conflict(&self) -> Option<(Action, &KeySpec)> {
+        let bindings = self.bindings();
+        bindings.iter().enumerate().find_map(|(i, (action, keys))| {
+            keys.iter()
+                .find(|spec| bindings[..i].iter().any(|(_, earlier)| earlier.contains(spec)))
+                .map(|spec| (*action, spec))
+        })
+    }
+
+    fn bindings(&self) -> [(Action, &[KeySpec]); 16] {
+        [
+            (Action::Up, &self.up),
+            (Action::Down, &self.down),
+            (Action::Left, &self.left),
+            (Action::Right, &self.right),
+            (Action::NextPanel, &self.next_panel),
+            (Action::Logs, &self.logs),
+            (Action::WorkflowFile, &self.workflow_file),
+            (Action::Rerun, &self.rerun),
+            (Action::RerunFailed, &self.rerun_failed),
+            (Action::Cancel, &self.cancel),
+            (Action::ForceCancel, &self.force_cancel),
+            (Action::Dispatch, &self.dispatch),
+            (Action::ToggleWorkflow, &self.toggle_workflow),
+            (Action::Login, &self.login),
+            (Action::Accounts, &self.accounts),
+            (Action::Quit, &self.quit),
+        ]
+    }
+}
+
+/// A key with modifiers, written like `"q"`, `"Up"`, `"PageDown"`, `"F5"` or
+/// `"Ctrl+r"`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeySpec {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Tab", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Space", KeyCode::Char(' ')),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+];
+
+impl KeySpec {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec;
+        // A lone "+" is a key, not a separator
+        while let Some((modifier, key)) = rest.split_once('+')
+            && !key.is_empty()
+        {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{}' in key '{}'", modifier, spec)),
+            };
+            rest = key;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(rest))
+                .map(|(_, code)| *code)
+                .or_else(|| {
+                    let number = rest.strip_prefix(['F', 'f'])?.parse().ok()?;
+                    (1..=12).contains(&number).then_some(KeyCode::F(number))
+                })
+                .ok_or_else(|| format!("unknown key '{}'", spec))?,
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    fn matches(&self, key: &KeyEvent) -> bool {
+        // Shifted characters arrive as the shifted char, with or without SHIFT
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl TryFrom<String> for KeySpec {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => {
+                let name = NAMED_KEYS
+                    .iter()
+                    .find(|(_, named)| *named == code)
+                    .map_or("?", |(name, _)| name);
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
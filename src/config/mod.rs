@@ -0,0 +1,516 @@
+// Built-in defaults. Most of these can be overridden from the config file,
+// environment variables or command line flags; see `Config`.
+
+// OAuth2 Configuration for GitHub
+pub const CLIENT_ID: &str = "YOUR_GITHUB_CLIENT_ID";
+pub const CLIENT_SECRET: &str = "YOUR_GITHUB_CLIENT_SECRET";
+
+pub const GITHUB_HOST: &str = "github.com";
+pub const GITHUB_API_BASE: &str = "https://api.github.com";
+
+pub const SCOPES: &[&str] = &["repo", "read:org", "workflow"];
+
+pub const DEFAULT_PORT: u16 = 8080;
+pub const PORT_RANGE: u16 = 100;
+pub const SERVER_TIMEOUT_SECS: u64 = 300;
+
+pub const SERVICE_NAME: &str = "oxa";
+pub const TOKEN_KEY: &str = "github_oauth_token";
+pub const ACCOUNTS_KEY: &str = "accounts";
+
+pub const CREDENTIALS_FILE: &str = "credentials.enc";
+pub const CREDENTIALS_KEY_FILE: &str = "credentials.key";
+pub const CONFIG_FILE: &str = "config.toml";
+pub const LOG_FILE: &str = "oxa.log";
+
+// Polling intervals for runs that are still queued or in progress, and for
+// everything else
+pub const ACTIVE_REFRESH_SECS: u64 = 5;
+pub const IDLE_REFRESH_SECS: u64 = 60;
+
+pub const APP_NAME: &str = "OXA - GitHub Actions TUI";
+
+mod keybindings;
+mod theme;
+
+pub use keybindings::{Action, KeyBindings};
+pub use theme::{Theme, ThemeName};
+
+use serde::Deserialize;
+use std::fs;
+use std::num::{NonZeroU16, NonZeroU64};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::app::Panel;
+use crate::cli::Cli;
+use crate::utils::error::AppError;
+use theme::ThemeOverrides;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Install the configuration loaded at startup. Only the first call has an
+/// effect.
+pub fn init(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// The active configuration, or the built-in defaults before [`init`].
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// User configuration, layered from the built-in defaults, the config file,
+/// environment variables and command line flags (later layers win).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub auth: AuthConfig,
+    pub api: ApiConfig,
+    pub refresh: RefreshConfig,
+    pub ui: UiConfig,
+    pub keybindings: KeyBindings,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+    /// First port tried for the local OAuth callback server.
+    pub callback_port: NonZeroU16,
+    /// How many ports after `callback_port` to try.
+    pub port_range: NonZeroU16,
+    pub server_timeout_secs: NonZeroU64,
+    pub credential_store: CredentialStoreKind,
+    /// Keyring service the credentials are stored under.
+    pub service_name: String,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            client_id: CLIENT_ID.to_string(),
+            client_secret: CLIENT_SECRET.to_string(),
+            scopes: SCOPES.iter().map(|scope| scope.to_string()).collect(),
+            callback_port: NonZeroU16::new(DEFAULT_PORT).unwrap(),
+            port_range: NonZeroU16::new(PORT_RANGE).unwrap(),
+            server_timeout_secs: NonZeroU64::new(SERVER_TIMEOUT_SECS).unwrap(),
+            credential_store: CredentialStoreKind::default(),
+            service_name: SERVICE_NAME.to_string(),
+        }
+    }
+}
+
+/// Where credentials are kept: the system keyring, an encrypted file under
+/// the config directory, or the keyring with the file as a fallback when no
+/// keyring service is running. The file is only encrypted against other
+/// local users when `OXA_PASSPHRASE` is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialStoreKind {
+    Keyring,
+    File,
+    #[default]
+    Auto,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    /// Web host new logins are made against.
+    pub host: String,
+    /// REST API base, when it isn't the usual one for `host`.
+    pub url: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            host: GITHUB_HOST.to_string(),
+            url: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RefreshConfig {
+    /// Seconds between polls while a run is queued or in progress.
+    pub active_secs: NonZeroU64,
+    /// Seconds between polls otherwise.
+    pub idle_secs: NonZeroU64,
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            active_secs: NonZeroU64::new(ACTIVE_REFRESH_SECS).unwrap(),
+            idle_secs: NonZeroU64::new(IDLE_REFRESH_SECS).unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Panel focused on startup.
+    pub default_panel: Panel,
+    pub theme: ThemeName,
+    /// Per-role colour overrides on top of `theme`.
+    pub colors: ThemeOverrides,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            default_panel: Panel::Repositories,
+            theme: ThemeName::default(),
+            colors: ThemeOverrides::default(),
+        }
+    }
+}
+
+impl UiConfig {
+    /// The selected theme with the user's colour overrides applied.
+    pub fn theme(&self) -> Theme {
+        self.theme.palette().with_overrides(&self.colors)
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/oxa/config.toml` or the platform equivalent.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(SERVICE_NAME).join(CONFIG_FILE))
+    }
+
+    /// Load the file given by `--config` (or the default one, if it exists)
+    /// and apply the environment and command line overrides from `cli`.
+    pub fn load(cli: &Cli) -> Result<Self, AppError> {
+        let mut config = match &cli.config {
+            Some(path) => Self::from_file(path)?,
+            None => match Self::default_path() {
+                Some(path) if path.exists() => Self::from_file(&path)?,
+                _ => Self::default(),
+            },
+        };
+        config.apply_overrides(cli);
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, AppError> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| AppError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        Self::parse(&source).map_err(|e| {
+            AppError::ConfigError(match e.position {
+                Some(position) => format!("{}:{}: {}", path.display(), position, e.message),
+                None => format!("{}: {}", path.display(), e.message),
+            })
+        })
+    }
+
+    /// Parse and validate a config file.
+    fn parse(source: &str) -> Result<Self, ConfigFileError> {
+        let config: Self = toml::from_str(source).map_err(|e| {
+            describe(source, e.span().map(|span| span.start), e.message())
+        })?;
+        config.validate(source)?;
+        Ok(config)
+    }
+
+    /// Checks that span several keys and so can't happen while deserializing.
+    fn validate(&self, source: &str) -> Result<(), ConfigFileError> {
+        if self.refresh.idle_secs < self.refresh.active_secs {
+            return Err(describe(
+                source,
+                find_key(source, "refresh", "idle_secs"),
+                "must not be shorter than refresh.active_secs",
+            ));
+        }
+        if self.auth.scopes.iter().any(|scope| scope.trim().is_empty()) {
+            return Err(describe(
+                source,
+                find_key(source, "auth", "scopes"),
+                "scopes must not be empty",
+            ));
+        }
+        if let Some((action, key)) = self.keybindings.conflict() {
+            return Err(describe(
+                source,
+                find_key(source, "keybindings", action.name()),
+                &format!("'{}' is already bound to another action", key),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Apply settings given through environment variables or flags.
+    fn apply_overrides(&mut self, cli: &Cli) {
+        if let Some(hostname) = &cli.hostname
+            && *hostname != self.api.host
+        {
+            // An API URL from the file belongs to the host it was written for
+            self.api.host = hostname.clone();
+            self.api.url = None;
+        }
+        if let Some(api_url) = &cli.api_url {
+            self.api.url = Some(api_url.clone());
+        }
+        if let Some(client_id) = &cli.client_id {
+            self.auth.client_id = client_id.clone();
+            self.auth.client_secret = String::new();
+        }
+        if let Some(client_secret) = &cli.client_secret {
+            self.auth.client_secret = client_secret.clone();
+        }
+        if let Some(credential_store) = cli.credential_store {
+            self.auth.credential_store = credential_store;
+        }
+        if let Some(theme) = cli.theme {
+            self.ui.theme = theme;
+        }
+    }
+
+    /// The host new logins are made against.
+    pub fn login_host(&self) -> GitHubHost {
+        let mut host = GitHubHost::new(&self.api.host, self.api.url.as_deref());
+        // Tokens from other OAuth apps remember theirs so they can be refreshed
+        if self.auth.client_id != CLIENT_ID {
+            host.client_id = Some(self.auth.client_id.clone());
+        }
+        host
+    }
+}
+
+/// A problem with a config file, located at the offending key when it is
+/// written out in the file.
+#[derive(Debug)]
+struct ConfigFileError {
+    /// `line:column`, e.g. `3:17`.
+    position: Option<String>,
+    /// The message, prefixed with the dotted key when known, e.g.
+    /// `ui.theme: unknown variant`.
+    message: String,
+}
+
+impl std::fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.position {
+            Some(position) => write!(f, "{}: {}", position, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Locate `message` at the line, column and dotted key at byte `offset`.
+fn describe(source: &str, offset: Option<usize>, message: &str) -> ConfigFileError {
+    let Some(offset) = offset.map(|offset| offset.min(source.len())) else {
+        return ConfigFileError {
+            position: None,
+            message: message.to_string(),
+        };
+    };
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = source[..offset].matches('\n').count() + 1;
+    let column = source[line_start..offset].chars().count() + 1;
+
+    ConfigFileError {
+        position: Some(format!("{}:{}", line, column)),
+        message: match key_at(source, line_start) {
+            Some(key) => format!("{}: {}", key, message),
+            None => message.to_string(),
+        },
+    }
+}
+
+/// The dotted key assigned on the line starting at `line_start`, qualified
+/// by the table header above it.
+fn key_at(source: &str, line_start: usize) -> Option<String> {
+    let line = source[line_start..].lines().next().unwrap_or("").trim();
+    if line.starts_with('[') {
+        return Some(table_name(line));
+    }
+    let key = line.split_once('=')?.0.trim().trim_matches('"');
+
+    let table = source[..line_start]
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|line| line.starts_with('['))
+        .map(table_name);
+    Some(match table {
+        Some(table) => format!("{}.{}", table, key),
+        None => key.to_string(),
+    })
+}
+
+fn table_name(header: &str) -> String {
+    header
+        .trim_start_matches('[')
+        .split(']')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_string()
+}
+
+/// Byte offset of `key` inside `[table]`, if it is written out there.
+fn find_key(source: &str, table: &str, key: &str) -> Option<usize> {
+    let mut current = String::new();
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            current = table_name(trimmed);
+        } else if current == table
+            && let Some((name, _)) = trimmed.split_once('=')
+            && name.trim().trim_matches('"') == key
+        {
+            return Some(offset + line.len() - line.trim_start().len());
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Endpoints of a GitHub instance: github.com or a GitHub Enterprise Server.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GitHubHost {
+    /// Host name of the web UI, e.g. `github.com` or `ghe.example.com`.
+    pub web_host: String,
+    /// REST API base, e.g. `https://ghe.example.com/api/v3`.
+    pub api_base: String,
+    /// OAuth app client ID registered on this host; the configured one when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// Secret of the OAuth app named by `client_id`. Never persisted.
+    #[serde(skip)]
+    pub client_secret: Option<String>,
+}
+
+impl Default for GitHubHost {
+    fn default() -> Self {
+        Self::new(GITHUB_HOST, None)
+    }
+}
+
+impl GitHubHost {
+    /// A host with an explicit API base, or the conventional one for
+    /// `web_host` (`https://<host>/api/v3` for Enterprise Server).
+    pub fn new(web_host: &str, api_base: Option<&str>) -> Self {
+        let web_host = web_host
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/')
+            .to_string();
+        let api_base = match api_base {
+            Some(api_base) => api_base.trim_end_matches('/').to_string(),
+            None if web_host == GITHUB_HOST => GITHUB_API_BASE.to_string(),
+            None => format!("https://{}/api/v3", web_host),
+        };
+
+        Self {
+            web_host,
+            api_base,
+            client_id: None,
+            client_secret: None,
+        }
+    }
+
+    pub fn client_id(&self) -> &str {
+        self.client_id.as_deref().unwrap_or(&get().auth.client_id)
+    }
+
+    /// The OAuth app secret, empty when the app doesn't use one.
+    pub fn client_secret(&self) -> &str {
+        match &self.client_secret {
+            Some(secret) => secret,
+            None if self.client_id() == get().auth.client_id => &get().auth.client_secret,
+            None => "",
+        }
+    }
+
+    pub fn auth_url(&self) -> String {
+        format!("https://{}/login/oauth/authorize", self.web_host)
+    }
+
+    pub fn token_url(&self) -> String {
+        format!("https://{}/login/oauth/access_token", self.web_host)
+    }
+
+    pub fn device_auth_url(&self) -> String {
+        format!("https://{}/login/device/code", self.web_host)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> String {
+        Config::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_defaults_from_empty_file() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.refresh.active_secs.get(), ACTIVE_REFRESH_SECS);
+        assert_eq!(config.api.host, GITHUB_HOST);
+    }
+
+    #[test]
+    fn reports_position_of_bad_value() {
+        let source = "[ui]\ntheme = \"neon\"\n";
+        let message = error(source);
+        assert!(message.starts_with("2:9: ui.theme: unknown variant `neon`"), "{}", message);
+    }
+
+    #[test]
+    fn reports_position_of_wrong_type() {
+        let source = "[auth]\nclient_id = \"abc\"\n  callback_port = 0\n";
+        let message = error(source);
+        assert!(message.starts_with("3:19: auth.callback_port: "), "{}", message);
+    }
+
+    #[test]
+    fn reports_position_of_unknown_key() {
+        let source = "[refresh]\nactive_secs = 5\nidle = 60\n";
+        let message = error(source);
+        assert!(message.starts_with("3:1: refresh.idle: unknown field `idle`"), "{}", message);
+    }
+
+    #[test]
+    fn reports_unknown_table() {
+        let source = "[api]\nhost = \"ghe.example.com\"\n\n[colours]\n";
+        let message = error(source);
+        assert!(message.starts_with("4:2: colours: unknown field `colours`"), "{}", message);
+    }
+
+    #[test]
+    fn reports_position_of_cross_key_failure() {
+        let source = "[refresh]\nactive_secs = 30\n    idle_secs = 10\n";
+        assert_eq!(
+            error(source),
+            "3:5: refresh.idle_secs: must not be shorter than refresh.active_secs",
+        );
+    }
+
+    #[test]
+    fn cross_key_failure_without_key_in_file() {
+        // idle_secs keeps its default of 60, so there is nothing to point at
+        let source = "[refresh]\nactive_secs = 120\n";
+        let error = Config::parse(source).unwrap_err();
+        assert_eq!(error.position, None);
+        assert_eq!(error.message, "must not be shorter than refresh.active_secs");
+    }
+
+    #[test]
+    fn find_key_only_matches_inside_its_table() {
+        let source = "[auth]\nscopes = [\"repo\"]\n[ui]\n\"scopes\" = 1\n";
+        assert_eq!(find_key(source, "auth", "scopes"), Some(7));
+        assert_eq!(find_key(source, "ui", "scopes"), Some(30));
+        assert_eq!(find_key(source, "refresh", "scopes"), None);
+    }
+}
@@ -0,0 +1,138 @@
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// Built-in colour schemes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    /// For dark terminal backgrounds.
+    #[default]
+    Dark,
+    /// For light terminal backgrounds.
+    Light,
+    /// Only the terminal's own foreground plus reverse video for selections.
+    Mono,
+}
+
+/// Colours the UI draws with, by role.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    /// Regular text.
+    pub text: Color,
+    /// Secondary text and unfocused borders.
+    pub muted: Color,
+    /// Least important text, such as repository flags.
+    pub dim: Color,
+    /// Focused borders, selections and key hints.
+    pub accent: Color,
+    /// Text drawn on an `accent` background.
+    pub on_accent: Color,
+    /// Names, branches and labels.
+    pub info: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub error: Color,
+    /// Work in progress, such as authentication and loading.
+    pub busy: Color,
+}
+
+impl ThemeName {
+    pub fn palette(self) -> Theme {
+        match self {
+            ThemeName::Dark => Theme {
+                text: Color::White,
+                muted: Color::Gray,
+                dim: Color::DarkGray,
+                accent: Color::Yellow,
+                on_accent: Color::Black,
+                info: Color::Cyan,
+                success: Color::Green,
+                warning: Color::Yellow,
+                error: Color::Red,
+                busy: Color::Blue,
+            },
+            ThemeName::Light => Theme {
+                text: Color::Black,
+                muted: Color::DarkGray,
+                dim: Color::Gray,
+                accent: Color::Blue,
+                on_accent: Color::White,
+                info: Color::Magenta,
+                success: Color::Green,
+                warning: Color::Rgb(175, 95, 0),
+                error: Color::Red,
+                busy: Color::Blue,
+            },
+            ThemeName::Mono => Theme {
+                text: Color::Reset,
+                muted: Color::Reset,
+                dim: Color::Reset,
+                accent: Color::White,
+                on_accent: Color::Black,
+                info: Color::Reset,
+                success: Color::Reset,
+                warning: Color::Reset,
+                error: Color::Reset,
+                busy: Color::Reset,
+            },
+        }
+    }
+}
+
+/// The `[ui.colors]` table: colours replacing single roles of the theme.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeOverrides {
+    text: Option<ThemeColor>,
+    muted: Option<ThemeColor>,
+    dim: Option<ThemeColor>,
+    accent: Option<ThemeColor>,
+    on_accent: Option<ThemeColor>,
+    info: Option<ThemeColor>,
+    success: Option<ThemeColor>,
+    warning: Option<ThemeColor>,
+    error: Option<ThemeColor>,
+    busy: Option<ThemeColor>,
+}
+
+impl Theme {
+    pub fn with_overrides(mut self, overrides: &ThemeOverrides) -> Self {
+        let roles = [
+            (&mut self.text, overrides.text),
+            (&mut self.muted, overrides.muted),
+            (&mut self.dim, overrides.dim),
+            (&mut self.accent, overrides.accent),
+            (&mut self.on_accent, overrides.on_accent),
+            (&mut self.info, overrides.info),
+            (&mut self.success, overrides.success),
+            (&mut self.warning, overrides.warning),
+            (&mut self.error, overrides.error),
+            (&mut self.busy, overrides.busy),
+        ];
+        for (color, replacement) in roles {
+            if let Some(ThemeColor(replacement)) = replacement {
+                *color = replacement;
+            }
+        }
+        self
+    }
+}
+
+/// A colour written as a name (`"yellow"`), `"#rrggbb"` or a 256-colour index.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+struct ThemeColor(Color);
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Color::from_str(&value).map(ThemeColor).map_err(|_| {
+            format!(
+                "unknown colour '{}' (use a name like \"yellow\", \"#rrggbb\" or 0-255)",
+                value
+            )
+        })
+    }
+}
@@ -1,163 +1,800 @@
-use anyhow::Result;
 use octocrab::{Octocrab, OctocrabBuilder, models::Repository};
-use chrono::{DateTime, Utc};
+use octocrab::etag::{EntityTag, Etagged};
+use octocrab::service::middleware::retry::RetryConfig;
+use chrono::{DateTime, TimeDelta, Utc};
+use http::{HeaderMap, Method, StatusCode};
+use http_body_util::BodyExt;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
+use crate::auth::{TokenData, TokenInfo};
+use crate::config::GitHubHost;
 use crate::utils::error::AppError;
+use crate::utils::retry::{INITIAL_RETRY_DELAY, MAX_RETRIES, retry_with_backoff};
+use super::rate_limit;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 
-#[derive(Debug, Clone)]
+/// Maximum page size accepted by the GitHub REST API.
+const PER_PAGE: u8 = 100;
+
+/// Workflow runs fetched per page; kept small so the first page of the
+/// Action Runs panel appears quickly.
+const RUNS_PER_PAGE: u32 = 30;
+
+/// Characters escaped in a segment of a URL path: all but the unreserved
+/// ones.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Repository affiliations listed for the authenticated user.
+const REPO_AFFILIATION: &str = "owner,collaborator,organization_member";
+
+/// A response to a GET request.
+struct Fetched {
+    status: StatusCode,
+    etag: Option<EntityTag>,
+    /// Headers of the API's response, before following any redirect.
+    headers: HeaderMap,
+    /// No body for 304 Not Modified. Kept as bytes, since a range of a
+    /// download can end inside a UTF-8 character.
+    body: Option<Vec<u8>>,
+}
+
+/// A repository and what is known about its Actions usage. Field names are
+/// part of the `--format json` output and must stay stable.
+#[derive(Debug, Clone, Serialize)]
 pub struct RepoWithActions {
-    pub repo_name: String,  // Use repo name instead of full Repository struct
-    pub has_actions: bool,
-    pub last_run: Option<WorkflowSummary>,
+    pub owner: String,
+    pub repo_name: String,
+    pub visibility: String,
+    pub default_branch: String,
+    pub archived: bool,
+    pub fork: bool,
+    /// `None` until the repository's workflow runs have been fetched.
+    pub has_actions: Option<bool>,
+    pub last_run: Option<WorkflowRun>,
     pub last_check: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone)]
-pub struct WorkflowSummary {
+impl RepoWithActions {
+    pub fn full_name(&self) -> String {
+        format!("{}/{}", self.owner, self.repo_name)
+    }
+
+    /// Notable properties: non-public visibility, fork and archived.
+    pub fn flags(&self) -> Vec<&str> {
+        let mut flags = Vec::new();
+        if self.visibility != "public" {
+            flags.push(self.visibility.as_str());
+        }
+        if self.fork {
+            flags.push("fork");
+        }
+        if self.archived {
+            flags.push("archived");
+        }
+        flags
+    }
+}
+
+impl From<Repository> for RepoWithActions {
+    fn from(repo: Repository) -> Self {
+        let owner = repo
+            .owner
+            .map(|owner| owner.login)
+            .or_else(|| {
+                repo.full_name
+                    .as_deref()
+                    .and_then(|full| full.split_once('/'))
+                    .map(|(owner, _)| owner.to_string())
+            })
+            .unwrap_or_default();
+        let visibility = repo.visibility.unwrap_or_else(|| {
+            if repo.private.unwrap_or(false) {
+                "private".to_string()
+            } else {
+                "public".to_string()
+            }
+        });
+
+        Self {
+            owner,
+            repo_name: repo.name,
+            visibility,
+            default_branch: repo.default_branch.unwrap_or_else(|| "main".to_string()),
+            archived: repo.archived.unwrap_or(false),
+            fork: repo.fork.unwrap_or(false),
+            has_actions: None,
+            last_run: None,
+            last_check: Utc::now(),
+        }
+    }
+}
+
+/// A workflow run. Field names are part of the `--format json` output and
+/// must stay stable.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowRun {
     pub id: u64,
+    pub run_number: u64,
     pub name: String,
+    /// The workflow definition the run was started from.
+    pub workflow_id: u64,
+    pub event: String,
+    pub head_branch: Option<String>,
+    pub head_sha: String,
+    pub actor: Option<String>,
     pub status: String,
     pub conclusion: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub run_started_at: Option<DateTime<Utc>>,
     pub html_url: String,
 }
 
+impl WorkflowRun {
+    /// The conclusion of a finished run, otherwise its status.
+    pub fn outcome(&self) -> &str {
+        self.conclusion.as_deref().unwrap_or(&self.status)
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.status == "completed"
+    }
+}
+
+/// A workflow defined by a file in `.github/workflows`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Workflow {
+    pub id: u64,
+    pub name: String,
+    pub path: String,
+    /// `active`, `disabled_manually` or `disabled_inactivity`, among others.
+    pub state: String,
+}
+
+impl Workflow {
+    pub fn is_active(&self) -> bool {
+        self.state == "active"
+    }
+}
+
+/// A job of a workflow run with its steps, as returned by the API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowJob {
+    pub id: u64,
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Unset until a runner picks the job up.
+    pub runner_name: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// A step of a workflow job.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkflowStep {
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl WorkflowJob {
+    pub fn is_completed(&self) -> bool {
+        self.status == "completed"
+    }
+
+    /// The conclusion of a finished job, otherwise its status.
+    pub fn outcome(&self) -> &str {
+        self.conclusion.as_deref().unwrap_or(&self.status)
+    }
+
+    /// Time spent running so far, or in total once completed.
+    pub fn duration(&self) -> Option<TimeDelta> {
+        elapsed(self.started_at, self.completed_at)
+    }
+}
+
+impl WorkflowStep {
+    /// The conclusion of a finished step, otherwise its status.
+    pub fn outcome(&self) -> &str {
+        self.conclusion.as_deref().unwrap_or(&self.status)
+    }
+
+    /// Time spent running so far, or in total once completed.
+    pub fn duration(&self) -> Option<TimeDelta> {
+        elapsed(self.started_at, self.completed_at)
+    }
+}
+
+fn elapsed(
+    started_at: Option<DateTime<Utc>>,
+    completed_at: Option<DateTime<Utc>>,
+) -> Option<TimeDelta> {
+    let started_at = started_at?;
+    Some(completed_at.unwrap_or_else(Utc::now) - started_at)
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowJobsResponse {
+    jobs: Vec<WorkflowJob>,
+}
+
+/// The route of a page of runs of `owner/repo`, or of just those of
+/// workflow `workflow_id`.
+fn runs_route(owner: &str, repo: &str, workflow_id: Option<u64>, page: u32) -> String {
+    let runs = match workflow_id {
+        Some(id) => format!("/repos/{}/{}/actions/workflows/{}/runs", owner, repo, id),
+        None => format!("/repos/{}/{}/actions/runs", owner, repo),
+    };
+    format!("{}?per_page={}&page={}", runs, RUNS_PER_PAGE, page)
+}
+
+/// One page of workflow runs together with the total number of runs the
+/// repository has, so callers know whether another page is available.
+#[derive(Debug, Clone)]
+pub struct WorkflowRunPage {
+    pub runs: Vec<WorkflowRun>,
+    pub total_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowsResponse {
+    workflows: Vec<Workflow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedItem {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EnvironmentsResponse {
+    environments: Vec<NamedItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentResponse {
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowRunsResponse {
+    total_count: u64,
+    workflow_runs: Vec<WorkflowRunResponse>,
+}
+
+/// The subset of the workflow run payload oxa uses. Octocrab's own `Run`
+/// model requires fields GitHub omits for some runs and has no `actor`.
+#[derive(Debug, Deserialize)]
+struct WorkflowRunResponse {
+    id: u64,
+    run_number: u64,
+    name: Option<String>,
+    workflow_id: u64,
+    event: String,
+    head_branch: Option<String>,
+    head_sha: String,
+    actor: Option<Actor>,
+    status: Option<String>,
+    conclusion: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    run_started_at: Option<DateTime<Utc>>,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Actor {
+    login: String,
+}
+
+impl From<WorkflowRunsResponse> for WorkflowRunPage {
+    fn from(response: WorkflowRunsResponse) -> Self {
+        Self {
+            runs: response
+                .workflow_runs
+                .into_iter()
+                .map(WorkflowRun::from)
+                .collect(),
+            total_count: response.total_count,
+        }
+    }
+}
+
+impl From<WorkflowRunResponse> for WorkflowRun {
+    fn from(run: WorkflowRunResponse) -> Self {
+        Self {
+            id: run.id,
+            run_number: run.run_number,
+            name: run.name.unwrap_or_else(|| "(unnamed workflow)".to_string()),
+            workflow_id: run.workflow_id,
+            event: run.event,
+            head_branch: run.head_branch,
+            head_sha: run.head_sha,
+            actor: run.actor.map(|actor| actor.login),
+            status: run.status.unwrap_or_else(|| "unknown".to_string()),
+            conclusion: run.conclusion,
+            created_at: run.created_at,
+            updated_at: run.updated_at,
+            run_started_at: run.run_started_at,
+            html_url: run.html_url,
+        }
+    }
+}
+
+/// A client for one account. Cheap to clone; clones share the connection
+/// pool.
+#[derive(Clone)]
 pub struct GitHubClient {
     client: Octocrab,
     pub username: String,
 }
 
 impl GitHubClient {
-    pub async fn new(access_token: &str) -> Result<Self, AppError> {
-        let client = OctocrabBuilder::new()
+    /// Client for `access_token`, asking the API whose token it is.
+    pub async fn new(host: &GitHubHost, access_token: &str) -> Result<Self, AppError> {
+        Ok(Self::validate(host, access_token).await?.0)
+    }
+
+    /// Client for `access_token` and what the API reports about the token:
+    /// whose it is, its scopes and when it expires.
+    pub async fn validate(
+        host: &GitHubHost,
+        access_token: &str,
+    ) -> Result<(Self, TokenInfo), AppError> {
+        let mut github = Self::build(host, access_token, String::new())?;
+        let token_info = github.token_info().await?;
+        github.username = token_info.username.clone();
+        Ok((github, token_info))
+    }
+
+    /// Look up the authenticated user. A rejected token is reported as
+    /// [`AppError::AuthFailed`].
+    async fn token_info(&self) -> Result<TokenInfo, AppError> {
+        let fetched = match self.send("/user", None).await {
+            Err(AppError::GitHubError(e))
+                if matches!(
+                    e.as_ref(),
+                    octocrab::Error::GitHub { source, .. }
+                        if source.status_code == StatusCode::UNAUTHORIZED
+                ) =>
+            {
+                return Err(AppError::AuthFailed(
+                    "GitHub rejected the token (bad credentials, revoked or expired)".to_string(),
+                ));
+            }
+            result => result?,
+        };
+        let user: Actor = serde_json::from_slice(&fetched.body.unwrap_or_default())?;
+        Ok(TokenInfo::from_headers(user.login, &fetched.headers))
+    }
+
+    /// Client for the instance and account `token_data` belongs to. The
+    /// login stored with the token is trusted, so no request is made.
+    pub fn from_token(token_data: &TokenData) -> Result<Self, AppError> {
+        Self::build(
+            &token_data.github_host(),
+            &token_data.access_token,
+            token_data.login.clone(),
+        )
+    }
+
+    fn build(host: &GitHubHost, access_token: &str, username: String) -> Result<Self, AppError> {
+        // Retries are ours, with backoff; octocrab's own retry immediately
+        let mut builder = OctocrabBuilder::new();
+        builder.add_retry_config(RetryConfig::None);
+        let client = builder
+            .base_uri(host.api_base.as_str())?
             .personal_token(access_token.to_string())
             .build()?;
-        
-        let user = client.current().user().await?;
-        
-        Ok(Self {
-            client,
-            username: user.login,
+        Ok(Self { client, username })
+    }
+
+    /// Send a GET request, retrying transient failures.
+    async fn send(&self, route: &str, headers: Option<HeaderMap>) -> Result<Fetched, AppError> {
+        retry_with_backoff(
+            || self.send_once(route, headers.clone()),
+            MAX_RETRIES,
+            INITIAL_RETRY_DELAY,
+        )
+        .await
+    }
+
+    /// Send a GET request once, recording the rate limit it reports.
+    /// Requests are not sent while GitHub is known to reject them.
+    async fn send_once(
+        &self,
+        route: &str,
+        headers: Option<HeaderMap>,
+    ) -> Result<Fetched, AppError> {
+        if let Some(until) = rate_limit::blocked_until() {
+            return Err(AppError::RateLimited(until));
+        }
+
+        let response = self.client._get_with_headers(route, headers.clone()).await?;
+        let status = response.status();
+        if let Some(until) = rate_limit::record(status, response.headers()) {
+            return Err(AppError::RateLimited(until));
+        }
+        if status.is_server_error() {
+            return Err(AppError::GitHubUnavailable(status.as_u16()));
+        }
+        let etag = EntityTag::extract_from_response(&response);
+        let response_headers = response.headers().clone();
+        if status == StatusCode::NOT_MODIFIED {
+            return Ok(Fetched {
+                status,
+                etag,
+                headers: response_headers,
+                body: None,
+            });
+        }
+        // Downloads such as logs redirect to short-lived storage URLs. The
+        // request headers go along so a `Range` still applies; octocrab
+        // leaves the token out for other hosts.
+        let location = response
+            .headers()
+            .get(http::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .filter(|_| status.is_redirection())
+            .map(str::to_string);
+        let response = match location {
+            Some(location) => self.client._get_with_headers(location, headers).await?,
+            None => response,
+        };
+        let status = response.status();
+        // Nothing past the start of a requested range
+        if status == StatusCode::RANGE_NOT_SATISFIABLE {
+            return Ok(Fetched {
+                status,
+                etag,
+                headers: response_headers,
+                body: Some(Vec::new()),
+            });
+        }
+
+        let response = octocrab::map_github_error(response).await?;
+        let body = response.into_body().collect().await?.to_bytes().to_vec();
+        Ok(Fetched {
+            status,
+            etag,
+            headers: response_headers,
+            body: Some(body),
         })
     }
-    
+
+    async fn get_json<T: DeserializeOwned>(&self, route: &str) -> Result<T, AppError> {
+        let body = self.send(route, None).await?.body.unwrap_or_default();
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Fetch every page of the list at `route`, which may carry a query
+    /// string of its own. `items` takes the list out of a page, for
+    /// endpoints that wrap it, e.g. in `{"total_count": 3, "jobs": [...]}`.
+    async fn get_all_pages<P, T>(
+        &self,
+        route: &str,
+        items: impl Fn(P) -> Vec<T>,
+    ) -> Result<Vec<T>, AppError>
+    where
+        P: DeserializeOwned,
+    {
+        let separator = if route.contains('?') { '&' } else { '?' };
+        let mut all = Vec::new();
+        for page in 1.. {
+            let batch = items(
+                self.get_json(&format!(
+                    "{}{}per_page={}&page={}",
+                    route, separator, PER_PAGE, page
+                ))
+                .await?,
+            );
+            let last = batch.len() < PER_PAGE as usize;
+            all.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(all)
+    }
+
+    /// Send a POST request once, recording the rate limit it reports. Not
+    /// retried, since the actions posted aren't safe to repeat.
+    async fn post(&self, route: &str, body: Option<&serde_json::Value>) -> Result<(), AppError> {
+        self.send_action(Method::POST, route, body).await
+    }
+
+    /// Send a PUT request once, like `post`.
+    async fn put(&self, route: &str) -> Result<(), AppError> {
+        self.send_action(Method::PUT, route, None).await
+    }
+
+    async fn send_action(
+        &self,
+        method: Method,
+        route: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<(), AppError> {
+        if let Some(until) = rate_limit::blocked_until() {
+            return Err(AppError::RateLimited(until));
+        }
+
+        let response = if method == Method::PUT {
+            self.client._put(route, body).await?
+        } else {
+            self.client._post(route, body).await?
+        };
+        let status = response.status();
+        if let Some(until) = rate_limit::record(status, response.headers()) {
+            return Err(AppError::RateLimited(until));
+        }
+        if status.is_server_error() {
+            return Err(AppError::GitHubUnavailable(status.as_u16()));
+        }
+        octocrab::map_github_error(response).await?;
+        Ok(())
+    }
+
+    /// List every repository the authenticated user owns, collaborates on or
+    /// can access through an organisation, following all result pages.
     pub async fn get_user_repos(&self) -> Result<Vec<RepoWithActions>, AppError> {
-        // For now, return mock data since GitHub API is complex
-        // TODO: Implement proper GitHub API integration
-        let mut mock_repos = Vec::new();
-        
-        // Create some mock repositories
-        for i in 1..=3 {
-            let has_actions = i % 2 == 0;
-            let conclusion = match i {
-                1 => Some("success"),
-                2 => Some("failure"), 
-                3 => Some("pending"),
-                _ => None,
-            };
-            
-            let last_run = if has_actions {
-                Some(WorkflowSummary {
-                    id: i as u64,
-                    name: format!("workflow_{}.yml", i),
-                    status: "completed".to_string(),
-                    conclusion: conclusion.map(|s| s.to_string()),
-                    created_at: chrono::Utc::now(),
-                    updated_at: chrono::Utc::now(),
-                    html_url: format!("https://github.com/{}/actions/runs/{}", self.username, i),
-                })
-            } else {
-                None
-            };
-            
-            // Create mock repository data
-            let repo_name = format!("repo-{}", i);
-            let has_actions = i % 2 == 0;
-            let conclusion = match i {
-                1 => Some("success"),
-                2 => Some("failure"), 
-                3 => Some("pending"),
-                _ => None,
-            };
-            
-            let last_run = if has_actions {
-                Some(WorkflowSummary {
-                    id: i as u64,
-                    name: format!("workflow_{}.yml", i),
-                    status: "completed".to_string(),
-                    conclusion: conclusion.map(|s| s.to_string()),
-                    created_at: chrono::Utc::now(),
-                    updated_at: chrono::Utc::now(),
-                    html_url: format!("https://github.com/{}/actions/runs/{}", "mock_user", i),
-                })
-            } else {
-                None
-            };
-            
-            mock_repos.push(RepoWithActions {
-                repo_name,
-                has_actions,
-                last_run,
-                last_check: chrono::Utc::now(),
-            });
+        let route = format!("/user/repos?affiliation={}&sort=full_name", REPO_AFFILIATION);
+        let repos: Vec<Repository> = self.get_all_pages(&route, |page| page).await?;
+        Ok(repos.into_iter().map(RepoWithActions::from).collect())
+    }
+
+    /// Fetch one page (1-based) of the workflow runs for `owner/repo`,
+    /// newest first, optionally only those of workflow `workflow_id`.
+    pub async fn get_repo_workflow_runs(
+        &self,
+        owner: &str,
+        repo: &str,
+        workflow_id: Option<u64>,
+        page: u32,
+    ) -> Result<WorkflowRunPage, AppError> {
+        let route = runs_route(owner, repo, workflow_id, page);
+        let response: WorkflowRunsResponse = self.get_json(&route).await?;
+        Ok(WorkflowRunPage::from(response))
+    }
+
+    /// Fetch the first page of workflow runs for `owner/repo` unless it is
+    /// unchanged since the response `etag` was taken from. Unchanged pages
+    /// come back without a value; GitHub doesn't count those requests
+    /// against the rate limit.
+    pub async fn get_latest_workflow_runs(
+        &self,
+        owner: &str,
+        repo: &str,
+        workflow_id: Option<u64>,
+        etag: Option<EntityTag>,
+    ) -> Result<Etagged<WorkflowRunPage>, AppError> {
+        let route = runs_route(owner, repo, workflow_id, 1);
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = etag {
+            EntityTag::insert_if_none_match_header(&mut headers, etag)?;
         }
-        
-        Ok(mock_repos)
-    }
-    
-    fn create_mock_repository(_name: &str) -> octocrab::models::Repository {
-        // Create a minimal mock repository - this is a placeholder
-        // In real implementation, this would come from GitHub API response
-        // For now, we need to figure out how to construct Repository properly
-        todo!("Proper Repository construction needed")
-    }
-    
-    async fn check_repo_has_workflows(&self, _repo: &Repository) -> Result<bool, AppError> {
-        // For now, return mock value
-        // TODO: Implement real workflow checking
-        Ok(true)
-    }
-    
-    async fn get_last_workflow_run(&self, _repo: &Repository) -> Result<WorkflowSummary, AppError> {
-        // For now, return mock workflow run
-        // TODO: Implement real workflow run fetching
-        Ok(WorkflowSummary {
-            id: 1,
-            name: "CI/CD".to_string(),
-            status: "completed".to_string(),
-            conclusion: Some("success".to_string()),
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
-            html_url: "https://github.com/example/actions".to_string(),
+
+        let response = self.send(&route, Some(headers)).await?;
+        let value = match response.body {
+            Some(body) => Some(WorkflowRunPage::from(
+                serde_json::from_slice::<WorkflowRunsResponse>(&body)?,
+            )),
+            None => None,
+        };
+        Ok(Etagged {
+            etag: response.etag,
+            value,
         })
     }
-    
-    pub async fn get_repo_workflows(&self, _repo: &Repository) -> Result<Vec<WorkflowSummary>, AppError> {
-        // For now, return mock workflow data
-        // TODO: Implement real workflow fetching
-        let mut workflows = Vec::new();
-        
-        // Create some mock workflow runs
-        for i in 1..=3 {
-            let conclusion = match i {
-                1 => Some("success".to_string()),
-                2 => Some("failure".to_string()),
-                3 => Some("pending".to_string()),
-                _ => None,
-            };
-            
-            workflows.push(WorkflowSummary {
-                id: i,
-                name: format!("Workflow {}.yml", i),
-                status: "completed".to_string(),
-                conclusion,
-                created_at: chrono::Utc::now(),
-                updated_at: chrono::Utc::now(),
-                html_url: format!("https://github.com/example/actions/run/{}", i),
-            });
+
+    /// List the jobs of the latest attempt of a workflow run, following all
+    /// result pages.
+    pub async fn get_run_jobs(
+        &self,
+        owner: &str,
+        repo: &str,
+        run_id: u64,
+    ) -> Result<Vec<WorkflowJob>, AppError> {
+        let route = format!(
+            "/repos/{}/{}/actions/runs/{}/jobs?filter=latest",
+            owner, repo, run_id
+        );
+        self.get_all_pages(&route, |page: WorkflowJobsResponse| page.jobs).await
+    }
+
+    /// Download the plain text log of job `job_id` in `owner/repo` from byte
+    /// `offset` on, so a running job's log can be followed without fetching
+    /// it all again. The bytes are returned as downloaded: the end of a
+    /// running job's log can cut a character in two.
+    pub async fn get_job_logs(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: u64,
+        offset: usize,
+    ) -> Result<Vec<u8>, AppError> {
+        let route = format!("/repos/{}/{}/actions/jobs/{}/logs", owner, repo, job_id);
+        let mut headers = HeaderMap::new();
+        if offset > 0 {
+            let range = format!("bytes={}-", offset);
+            headers.insert(
+                http::header::RANGE,
+                range.parse().expect("valid range header"),
+            );
         }
-        
-        Ok(workflows)
+
+        let response = self.send(&route, Some(headers)).await?;
+        let mut body = response.body.unwrap_or_default();
+        // Without partial content support the whole log comes back
+        if offset > 0 && response.status == StatusCode::OK {
+            body.drain(..offset.min(body.len()));
+        }
+        Ok(body)
+    }
+
+    /// Fetch a single workflow run of `owner/repo`.
+    pub async fn get_workflow_run(
+        &self,
+        owner: &str,
+        repo: &str,
+        run_id: u64,
+    ) -> Result<WorkflowRun, AppError> {
+        let route = format!("/repos/{}/{}/actions/runs/{}", owner, repo, run_id);
+        let run: WorkflowRunResponse = self.get_json(&route).await?;
+        Ok(WorkflowRun::from(run))
+    }
+
+    /// List the workflows defined in `owner/repo`.
+    pub async fn get_repo_workflows(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<Workflow>, AppError> {
+        let route = format!("/repos/{}/{}/actions/workflows", owner, repo);
+        self.get_all_pages(&route, |page: WorkflowsResponse| page.workflows).await
+    }
+
+    /// Enable or disable workflow `workflow_id` of `owner/repo`. Disabled
+    /// workflows aren't triggered by any event.
+    pub async fn set_workflow_enabled(
+        &self,
+        owner: &str,
+        repo: &str,
+        workflow_id: u64,
+        enabled: bool,
+    ) -> Result<(), AppError> {
+        let action = if enabled { "enable" } else { "disable" };
+        let route = format!(
+            "/repos/{}/{}/actions/workflows/{}/{}",
+            owner, repo, workflow_id, action
+        );
+        self.put(&route).await
+    }
+
+    /// Names of the branches and tags of `owner/repo`, branches first.
+    pub async fn get_refs(&self, owner: &str, repo: &str) -> Result<Vec<String>, AppError> {
+        let branches: Vec<NamedItem> = self
+            .get_all_pages(&format!("/repos/{}/{}/branches", owner, repo), |page| page)
+            .await?;
+        let tags: Vec<NamedItem> = self
+            .get_all_pages(&format!("/repos/{}/{}/tags", owner, repo), |page| page)
+            .await?;
+        Ok(branches.into_iter().chain(tags).map(|item| item.name).collect())
+    }
+
+    /// Names of the deployment environments of `owner/repo`, following all
+    /// result pages.
+    pub async fn get_environments(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<Vec<String>, AppError> {
+        let route = format!("/repos/{}/{}/environments", owner, repo);
+        let environments = self
+            .get_all_pages(&route, |page: EnvironmentsResponse| page.environments)
+            .await?;
+        Ok(environments.into_iter().map(|environment| environment.name).collect())
+    }
+
+    /// The text of file `path` in `owner/repo` at `git_ref`.
+    pub async fn get_file(
+        &self,
+        owner: &str,
+        repo: &str,
+        path: &str,
+        git_ref: &str,
+    ) -> Result<String, AppError> {
+        // File names may hold spaces, `#`, `?` or `%`
+        let encoded_path = path
+            .split('/')
+            .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        let route = format!(
+            "/repos/{}/{}/contents/{}?ref={}",
+            owner,
+            repo,
+            encoded_path,
+            url::form_urlencoded::byte_serialize(git_ref.as_bytes()).collect::<String>()
+        );
+        let response: ContentResponse = self.get_json(&route).await?;
+        // The content is base64 wrapped at 60 columns
+        let encoded: String = response.content.split_whitespace().collect();
+        let bytes = BASE64
+            .decode(encoded)
+            .map_err(|e| AppError::ServerError(format!("invalid file content: {}", e)))?;
+        String::from_utf8(bytes)
+            .map_err(|_| AppError::ServerError(format!("{} is not UTF-8 text", path)))
+    }
+
+    /// Start workflow `workflow_id` of `owner/repo` on `git_ref` with
+    /// `inputs`.
+    pub async fn dispatch_workflow(
+        &self,
+        owner: &str,
+        repo: &str,
+        workflow_id: u64,
+        git_ref: &str,
+        inputs: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), AppError> {
+        let route = format!(
+            "/repos/{}/{}/actions/workflows/{}/dispatches",
+            owner, repo, workflow_id
+        );
+        let body = serde_json::json!({ "ref": git_ref, "inputs": inputs });
+        self.post(&route, Some(&body)).await
+    }
+
+    /// Re-run workflow run `run_id` of `owner/repo`: every job, or with
+    /// `failed_only` just the failed jobs and those depending on them.
+    pub async fn rerun_workflow_run(
+        &self,
+        owner: &str,
+        repo: &str,
+        run_id: u64,
+        failed_only: bool,
+        debug_logging: bool,
+    ) -> Result<(), AppError> {
+        let action = if failed_only { "rerun-failed-jobs" } else { "rerun" };
+        let route = format!("/repos/{}/{}/actions/runs/{}/{}", owner, repo, run_id, action);
+        let body = serde_json::json!({ "enable_debug_logging": debug_logging });
+        self.post(&route, Some(&body)).await
     }
-}
\ No newline at end of file
+
+    /// Re-run job `job_id` of `owner/repo` and the jobs depending on it.
+    pub async fn rerun_job(
+        &self,
+        owner: &str,
+        repo: &str,
+        job_id: u64,
+        debug_logging: bool,
+    ) -> Result<(), AppError> {
+        let route = format!("/repos/{}/{}/actions/jobs/{}/rerun", owner, repo, job_id);
+        let body = serde_json::json!({ "enable_debug_logging": debug_logging });
+        self.post(&route, Some(&body)).await
+    }
+
+    /// Cancel workflow run `run_id` of `owner/repo`. With `force`, jobs are
+    /// stopped even if their conditions, such as `always()`, would keep them
+    /// running.
+    pub async fn cancel_workflow_run(
+        &self,
+        owner: &str,
+        repo: &str,
+        run_id: u64,
+        force: bool,
+    ) -> Result<(), AppError> {
+        let action = if force { "force-cancel" } else { "cancel" };
+        let route = format!("/repos/{}/{}/actions/runs/{}/{}", owner, repo, run_id, action);
+        self.post(&route, None).await
+    }
+}
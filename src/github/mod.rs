@@ -1,3 +1,6 @@
 pub mod client;
+pub mod rate_limit;
 
-pub use client::{GitHubClient, RepoWithActions, WorkflowSummary};
\ No newline at end of file
+pub use client::{
+    GitHubClient, RepoWithActions, Workflow, WorkflowJob, WorkflowRun, WorkflowRunPage,
+};
@@ -1,24 +0,0 @@
-use serde::{Deserialize, Serialize};
-
-pub use client::{EnhancedWorkflowRun, GitHubClient, RepoWithActions, WorkflowStatus};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GitHubUser {
-    pub login: String,
-    pub id: u64,
-    pub name: Option<String>,
-    pub email: Option<String>,
-    pub avatar_url: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorkflowSummary {
-    pub id: u64,
-    pub name: String,
-    pub path: String,
-    pub state: String,
-    pub created_at: String,
-    pub updated_at: String,
-    pub url: String,
-    pub html_url: String,
-}
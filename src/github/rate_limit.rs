@@ -0,0 +1,244 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use http::{HeaderMap, StatusCode};
+use std::sync::Mutex;
+
+/// Remaining requests below which background polling pauses, leaving the
+/// rest of the budget for things the user asks for.
+const LOW_REMAINING: u64 = 50;
+
+/// The core REST API budget as of the latest response.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    pub fn is_low(&self) -> bool {
+        self.remaining <= LOW_REMAINING.min(self.limit / 10)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Tracker {
+    budget: Option<RateLimit>,
+    /// End of a secondary rate limit, from a `retry-after` header.
+    retry_after: Option<DateTime<Utc>>,
+}
+
+/// Shared by every client, since the budget belongs to the account rather
+/// than to a client, and accounts can be switched.
+static TRACKER: Mutex<Tracker> = Mutex::new(Tracker {
+    budget: None,
+    retry_after: None,
+});
+
+fn tracker() -> std::sync::MutexGuard<'static, Tracker> {
+    TRACKER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// The budget reported by the latest response, if any was seen.
+pub fn latest() -> Option<RateLimit> {
+    tracker().budget
+}
+
+/// Forget what is known, e.g. after switching to an account with its own
+/// budget.
+pub fn clear() {
+    *tracker() = Tracker::default();
+}
+
+/// When requests may be made again, if GitHub would currently reject them.
+pub fn blocked_until() -> Option<DateTime<Utc>> {
+    let tracker = tracker();
+    let now = Utc::now();
+    let exhausted = tracker
+        .budget
+        .filter(|budget| budget.remaining == 0)
+        .map(|budget| budget.reset);
+    [tracker.retry_after, exhausted]
+        .into_iter()
+        .flatten()
+        .filter(|until| *until > now)
+        .max()
+}
+
+/// When background polling may resume: like [`blocked_until`], but also
+/// while the budget is nearly spent.
+pub fn polling_paused_until() -> Option<DateTime<Utc>> {
+    let low = latest()
+        .filter(|budget| budget.is_low() && budget.reset > Utc::now())
+        .map(|budget| budget.reset);
+    blocked_until().max(low)
+}
+
+/// Record the rate limit headers of a response. Returns when requests may
+/// be made again if the response is a rate limit rejection.
+pub(super) fn record(status: StatusCode, headers: &HeaderMap) -> Option<DateTime<Utc>> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+
+    let mut tracker = tracker();
+    if let (Some(limit), Some(remaining), Some(reset)) = (
+        header("x-ratelimit-limit"),
+        header("x-ratelimit-remaining"),
+        header("x-ratelimit-reset"),
+    ) && let Some(reset) = DateTime::from_timestamp(reset as i64, 0)
+    {
+        tracker.budget = Some(RateLimit {
+            limit,
+            remaining,
+            reset,
+        });
+    }
+
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    // Secondary limits say how long to back off; a spent budget lasts until
+    // its reset
+    if let Some(secs) = header("retry-after") {
+        let until = Utc::now() + TimeDelta::seconds(secs as i64);
+        tracker.retry_after = Some(until);
+        return Some(until);
+    }
+    tracker
+        .budget
+        .filter(|budget| budget.remaining == 0)
+        .map(|budget| budget.reset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    /// The tracker is global, so tests that record headers take turns.
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    fn serial() -> std::sync::MutexGuard<'static, ()> {
+        let guard = SERIAL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        clear();
+        guard
+    }
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| {
+                (http::HeaderName::from_static(name), HeaderValue::from_str(value).unwrap())
+            })
+            .collect()
+    }
+
+    fn budget(limit: u64, remaining: u64, reset: DateTime<Utc>) -> HeaderMap {
+        headers(&[
+            ("x-ratelimit-limit", limit.to_string()),
+            ("x-ratelimit-remaining", remaining.to_string()),
+            ("x-ratelimit-reset", reset.timestamp().to_string()),
+        ])
+    }
+
+    fn in_an_hour() -> DateTime<Utc> {
+        DateTime::from_timestamp((Utc::now() + TimeDelta::hours(1)).timestamp(), 0).unwrap()
+    }
+
+    #[test]
+    fn records_budget_headers() {
+        let _serial = serial();
+        let reset = in_an_hour();
+        assert_eq!(record(StatusCode::OK, &budget(5000, 4321, reset)), None);
+
+        let latest = latest().unwrap();
+        assert_eq!((latest.limit, latest.remaining, latest.reset), (5000, 4321, reset));
+        assert_eq!(blocked_until(), None);
+        assert_eq!(polling_paused_until(), None);
+    }
+
+    #[test]
+    fn ignores_incomplete_or_malformed_headers() {
+        let _serial = serial();
+        let mut headers = budget(5000, 10, in_an_hour());
+        headers.remove("x-ratelimit-reset");
+        record(StatusCode::OK, &headers);
+        assert!(latest().is_none());
+
+        let mut headers = budget(5000, 10, in_an_hour());
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("lots"));
+        record(StatusCode::OK, &headers);
+        assert!(latest().is_none());
+    }
+
+    #[test]
+    fn low_threshold_is_50_or_a_tenth_of_the_limit() {
+        let reset = in_an_hour();
+        let low = |limit, remaining| {
+            RateLimit {
+                limit,
+                remaining,
+                reset,
+            }
+            .is_low()
+        };
+        assert!(low(5000, 50));
+        assert!(!low(5000, 51));
+        // Small budgets, e.g. unauthenticated ones, pause at a tenth
+        assert!(low(60, 6));
+        assert!(!low(60, 7));
+    }
+
+    #[test]
+    fn low_budget_pauses_polling_until_reset() {
+        let _serial = serial();
+        let reset = in_an_hour();
+        record(StatusCode::OK, &budget(5000, 50, reset));
+        assert_eq!(polling_paused_until(), Some(reset));
+        // Requests the user makes still go through
+        assert_eq!(blocked_until(), None);
+
+        // A budget that has already reset doesn't pause anything
+        let past = DateTime::from_timestamp(Utc::now().timestamp() - 60, 0).unwrap();
+        record(StatusCode::OK, &budget(5000, 50, past));
+        assert_eq!(polling_paused_until(), None);
+    }
+
+    #[test]
+    fn spent_budget_blocks_until_reset() {
+        let _serial = serial();
+        let reset = in_an_hour();
+        let until = record(StatusCode::FORBIDDEN, &budget(5000, 0, reset));
+        assert_eq!(until, Some(reset));
+        assert_eq!(blocked_until(), Some(reset));
+        assert_eq!(polling_paused_until(), Some(reset));
+    }
+
+    #[test]
+    fn forbidden_with_budget_left_is_not_a_rate_limit() {
+        let _serial = serial();
+        let until = record(StatusCode::FORBIDDEN, &budget(5000, 4000, in_an_hour()));
+        assert_eq!(until, None);
+        assert_eq!(blocked_until(), None);
+    }
+
+    #[test]
+    fn retry_after_sets_secondary_limit() {
+        let _serial = serial();
+        let before = Utc::now();
+        let mut headers = budget(5000, 4000, in_an_hour());
+        headers.insert("retry-after", HeaderValue::from_static("30"));
+
+        // Only rejections are rate limits
+        assert_eq!(record(StatusCode::OK, &headers), None);
+        assert_eq!(blocked_until(), None);
+
+        let until = record(StatusCode::TOO_MANY_REQUESTS, &headers).unwrap();
+        assert!(until >= before + TimeDelta::seconds(30));
+        assert!(until <= Utc::now() + TimeDelta::seconds(30));
+        assert_eq!(blocked_until(), Some(until));
+    }
+}
@@ -1,30 +1,78 @@
 // GitHub Actions TUI Application
 use anyhow::Result;
 use std::io;
+use std::time::{Duration, Instant};
+use chrono::Utc;
 use ratatui::{
     backend::CrosstermBackend,
     Terminal,
 };
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{DisableMouseCapture, EnableMouseCapture, Event as TerminalEvent, KeyCode, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
 mod app;
 mod auth;
+mod cli;
+mod commands;
 mod github;
 mod ui;
 mod utils;
 mod config;
+mod workflow;
 
-use app::{AppState, AuthState};
-use auth::{TokenManager, auth_code_flow};
-use github::GitHubClient;
+use app::log_view::LogState;
+use app::yaml_view::YamlState;
+use app::{
+    AppState, AuthState, Dispatch, Event, EventLoop, LogChunk, LogView, Panel, PendingDispatch,
+    Popup, RunAction, Session, TaskKind, TaskResult, Tasks, WorkflowSource, YamlView,
+};
+use app::dispatch::DispatchStep;
+use auth::{
+    Account, AuthCodeLogin, DeviceAuthorization, DevicePoll, TokenData, TokenInfo, TokenManager,
+    finish_auth_code_flow, personal_token_login, poll_device_token, request_device_code,
+    start_auth_code_flow, validate_token,
+};
+use clap::Parser;
+use cli::{Cli, Command};
+use config::{Action, Config};
+use github::rate_limit;
 use utils::error::AppError;
+use workflow::InputKind;
+
+/// How long to wait before trying again when a token fails to refresh.
+const TOKEN_REFRESH_RETRY: Duration = Duration::from_secs(60);
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match Config::load(&cli) {
+        Ok(config) => config::init(config),
+        Err(e) => {
+            eprintln!("oxa: {}", e);
+            std::process::exit(commands::EXIT_USAGE);
+        }
+    }
+
+    utils::logging::init();
+
+    if let Some(command) = &cli.command
+        && !matches!(command, Command::Tui)
+    {
+        std::process::exit(commands::run(&cli, command).await);
+    }
+
+    // Open the credential store first, so its errors reach a normal terminal
+    let token_manager = match TokenManager::new() {
+        Ok(token_manager) => token_manager,
+        Err(e) => {
+            eprintln!("oxa: {}", e);
+            std::process::exit(commands::EXIT_ERROR);
+        }
+    };
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -34,17 +82,37 @@ async fn main() -> Result<()> {
 
     // Initialize application state
     let mut app = AppState::new();
-    let token_manager = TokenManager::new()?;
+    let mut events = EventLoop::new();
+    let mut tasks = Tasks::new(events.sender());
 
-    // Mock data for testing - disabled for now
-    // app.repos.push(create_mock_repo("oxa", true, Some("success")));
-    // app.repos.push(create_mock_repo("test-repo", true, Some("failure")));
-    // app.repos.push(create_mock_repo("no-actions", false, None));
-    // app.auth_state = AuthState::Authenticated { username: "testuser".to_string() };
-    app.auth_state = AuthState::Unauthenticated;
+    // A token given on the command line or in the environment logs in directly,
+    // otherwise the session stored by a previous run is validated in the background
+    match cli.supplied_token() {
+        Ok(Some(token)) => login_with_token(&mut app, &mut tasks, token, false),
+        Ok(None) => match token_manager.get_token() {
+            Ok(Some(_)) => {
+                app.auth_state = AuthState::Authenticating;
+                tasks.spawn(TaskKind::Session, async {
+                    TaskResult::SessionRestored(restore_session().await)
+                });
+            }
+            Ok(None) => {}
+            Err(e) => {
+                app.auth_state = AuthState::Error {
+                    message: e.user_message(),
+                };
+            }
+        },
+        Err(e) => {
+            app.auth_state = AuthState::Error {
+                message: e.user_message(),
+            };
+        }
+    }
 
     // Main application loop
-    let res = run_app(&mut terminal, &mut app).await;
+    let res = run_app(&mut terminal, &mut app, &mut events, &mut tasks).await;
+    tasks.cancel_all();
 
     // Restore terminal
     disable_raw_mode()?;
@@ -62,171 +130,1490 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-// Mock repository function commented out due to Repository struct construction issues
-// In a real implementation, this would fetch data from GitHub API
-// Mock repository creation commented out due to Repository struct construction issues
-// In real implementation, this would fetch data from GitHub API
-// fn create_mock_repo(name: &str, has_actions: bool, conclusion: Option<&str>) -> crate::github::RepoWithActions {
-//     todo!("Implement proper repository mocking")
-// }
-
-async fn load_repositories(app: &mut AppState) -> Result<(), AppError> {
-    if let Ok(token_manager) = TokenManager::new() {
-        if let Ok(Some(token_data)) = token_manager.get_token() {
-            // Load repositories using stored token
-            app.set_loading(true);
-            
-            match GitHubClient::new(&token_data.access_token).await {
-                Ok(client) => {
-                    match client.get_user_repos().await {
-                        Ok(repos) => {
-                            app.repos = repos;
-                        }
-                        Err(e) => {
-                            app.auth_state = AuthState::Error { 
-                                message: format!("Failed to load repositories: {}", e) 
-                            };
+/// Fetch the user's repositories in the background.
+fn load_repositories(app: &mut AppState, tasks: &mut Tasks) {
+    let client = app.client();
+    app.loading_repos = true;
+    tasks.spawn(TaskKind::Repos, async {
+        let result = async { client?.get_user_repos().await }.await;
+        TaskResult::ReposLoaded(result)
+    });
+}
+
+/// Load the workflows of the selected repository, listing all runs again.
+fn load_workflows(app: &mut AppState, tasks: &mut Tasks) {
+    tasks.cancel(TaskKind::Workflows);
+    app.clear_workflows();
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    let full_name = repo.full_name();
+
+    let client = app.client();
+    app.loading_workflows = true;
+    tasks.spawn(TaskKind::Workflows, async move {
+        let result = async {
+            client?
+                .get_repo_workflows(&owner, &name)
+                .await
+        }
+        .await;
+        TaskResult::WorkflowsLoaded {
+            repo: full_name,
+            result,
+        }
+    });
+}
+
+/// Enable the selected workflow if it is disabled, otherwise disable it.
+/// The list shows the new state straight away.
+fn toggle_workflow(app: &mut AppState, tasks: &mut Tasks) {
+    if tasks.is_running(TaskKind::WorkflowState) {
+        return;
+    }
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    let Some(workflow) = app.selected_workflow.and_then(|i| app.workflows.get_mut(i)) else {
+        return;
+    };
+    let workflow_id = workflow.id;
+    let enable = !workflow.is_active();
+    let new_state = if enable { "active" } else { "disabled_manually" };
+    let previous = std::mem::replace(&mut workflow.state, new_state.to_string());
+    let client = app.client();
+
+    tasks.spawn(TaskKind::WorkflowState, async move {
+        let result = async {
+            client?
+                .set_workflow_enabled(&owner, &name, workflow_id, enable)
+                .await
+        }
+        .await;
+        TaskResult::WorkflowStateChanged {
+            workflow_id,
+            previous,
+            result,
+        }
+    });
+}
+
+/// Load the first page of workflow runs for the selected repository,
+/// replacing whatever runs were shown before. A load still in flight for the
+/// previous selection is cancelled.
+fn load_workflow_actions(app: &mut AppState, tasks: &mut Tasks) {
+    tasks.cancel(TaskKind::Runs);
+    tasks.cancel(TaskKind::RunsPage);
+    app.clear_actions();
+    app.loading_actions = refresh_workflow_runs(app, tasks);
+}
+
+/// Poll the selected repository's runs once the refresh interval is up,
+/// unless the rate limit is nearly spent.
+fn refresh_workflow_runs_if_due(app: &mut AppState, tasks: &mut Tasks) {
+    // Polling resumes on its own once the rate limit resets
+    if app
+        .next_refresh
+        .is_some_and(|due| Instant::now() >= due && !tasks.is_running(TaskKind::Runs))
+        && rate_limit::polling_paused_until().is_none()
+    {
+        app.next_refresh = None;
+        refresh_workflow_runs(app, tasks);
+    }
+}
+
+/// Fetch the first page of the selected repository's runs if it changed
+/// since the last fetch. Returns whether a fetch was started.
+fn refresh_workflow_runs(app: &mut AppState, tasks: &mut Tasks) -> bool {
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return false;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    let full_name = repo.full_name();
+    let workflow_id = app.workflow_filter().map(|workflow| workflow.id);
+    let etag = app.runs_etag.clone();
+    let client = app.client();
+
+    tasks.spawn(TaskKind::Runs, async move {
+        let result = async {
+            client?
+                .get_latest_workflow_runs(&owner, &name, workflow_id, etag)
+                .await
+        }
+        .await;
+        TaskResult::RunsRefreshed {
+            repo: full_name,
+            workflow_id,
+            result,
+        }
+    });
+    true
+}
+
+/// Append the next page of workflow runs for the selected repository.
+fn load_more_workflow_actions(app: &mut AppState, tasks: &mut Tasks) {
+    if !app.has_more_actions() || tasks.is_running(TaskKind::RunsPage) {
+        return;
+    }
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    let full_name = repo.full_name();
+    let workflow_id = app.workflow_filter().map(|workflow| workflow.id);
+    let page = app.actions_page + 1;
+    let client = app.client();
+
+    app.loading_actions = true;
+    tasks.spawn(TaskKind::RunsPage, async move {
+        let result = async {
+            client?
+                .get_repo_workflow_runs(&owner, &name, workflow_id, page)
+                .await
+        }
+        .await;
+        TaskResult::RunsLoaded {
+            repo: full_name,
+            page,
+            result,
+        }
+    });
+}
+
+/// Fetch the jobs of the selected run unless they are already shown or on
+/// their way.
+fn load_jobs_if_needed(app: &mut AppState, tasks: &mut Tasks) {
+    let run_id = app.selected_run().map(|run| run.id);
+    if run_id == app.jobs_run_id {
+        return;
+    }
+    tasks.cancel(TaskKind::Jobs);
+    app.clear_jobs();
+    if let Some(run_id) = run_id {
+        app.jobs_run_id = Some(run_id);
+        app.loading_jobs = fetch_jobs(app, tasks, run_id);
+    }
+}
+
+/// Fetch the jobs of `run_id` in the selected repository. Returns whether a
+/// fetch was started.
+fn fetch_jobs(app: &AppState, tasks: &mut Tasks, run_id: u64) -> bool {
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return false;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    let client = app.client();
+
+    tasks.spawn(TaskKind::Jobs, async move {
+        let result = async {
+            client?
+                .get_run_jobs(&owner, &name, run_id)
+                .await
+        }
+        .await;
+        TaskResult::JobsLoaded { run_id, result }
+    });
+    true
+}
+
+/// Open the log of the selected job and download it.
+fn open_job_log(app: &mut AppState, tasks: &mut Tasks) {
+    let Some(job) = app.selected_job.and_then(|i| app.jobs.get(i)) else {
+        return;
+    };
+    app.log_view = Some(LogView::new(job.id, job.name.clone(), !job.is_completed()));
+    fetch_log(app, tasks);
+}
+
+/// Download the output of the open log past what was already received.
+fn fetch_log(app: &mut AppState, tasks: &mut Tasks) {
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    let client = app.client();
+    let Some(view) = app.log_view.as_mut() else {
+        return;
+    };
+    let job_id = view.job_id;
+    let offset = view.offset;
+    let complete = !view.live;
+    view.next_poll = None;
+
+    tasks.spawn(TaskKind::Logs, async move {
+        let result = async {
+            let bytes = client?
+                .get_job_logs(&owner, &name, job_id, offset)
+                .await?;
+            Ok(LogChunk::parse(&bytes, complete))
+        }
+        .await;
+        TaskResult::LogLoaded {
+            job_id,
+            offset,
+            result,
+        }
+    });
+}
+
+/// Poll the log of a running job for new output. Once the job has finished,
+/// as the Details panel's jobs and runs tell, the rest is fetched one last
+/// time.
+fn refresh_log_if_due(app: &mut AppState, tasks: &mut Tasks) {
+    let Some(view) = app.log_view.as_mut() else {
+        return;
+    };
+    if view.next_poll.is_none_or(|due| Instant::now() < due)
+        || tasks.is_running(TaskKind::Logs)
+        || rate_limit::polling_paused_until().is_some()
+    {
+        return;
+    }
+
+    let job_done = app
+        .jobs
+        .iter()
+        .find(|job| job.id == view.job_id)
+        .is_none_or(|job| job.is_completed());
+    // Jobs stop being polled once their run completes, so the run has the
+    // final say
+    let run_done = app
+        .jobs_run_id
+        .and_then(|run_id| app.actions.iter().find(|run| run.id == run_id))
+        .is_none_or(|run| run.is_completed());
+    if job_done || run_done {
+        view.live = false;
+    }
+    fetch_log(app, tasks);
+}
+
+/// Handle a key while a job log is open. The log view has fixed keys, like
+/// the popups.
+fn handle_log_key(app: &mut AppState, tasks: &mut Tasks, code: KeyCode) {
+    let Some(view) = app.log_view.as_mut() else {
+        return;
+    };
+    view.message = None;
+
+    if let Some(input) = view.input.as_mut() {
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let query = view.input.take().unwrap_or_default();
+                view.search(query);
+                view.update_following();
+            }
+            KeyCode::Esc => view.input = None,
+            _ => {}
+        }
+        return;
+    }
+    if matches!(code, KeyCode::Esc | KeyCode::Char('q')) {
+        tasks.cancel(TaskKind::Logs);
+        app.log_view = None;
+        return;
+    }
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => view.move_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => view.move_cursor(1),
+        KeyCode::PageUp => view.page(-1),
+        KeyCode::PageDown => view.page(1),
+        KeyCode::Home | KeyCode::Char('g') => view.jump_to_start(),
+        KeyCode::End | KeyCode::Char('G') => view.jump_to_end(),
+        KeyCode::Enter | KeyCode::Char(' ') => view.toggle_fold(),
+        KeyCode::Char('z') => view.toggle_all_folds(),
+        KeyCode::Char('e') => view.next_error(true),
+        KeyCode::Char('E') => view.next_error(false),
+        KeyCode::Char('/') => view.input = Some(String::new()),
+        KeyCode::Char('n') => view.next_match(true),
+        KeyCode::Char('N') => view.next_match(false),
+        KeyCode::Char('t') => view.show_timestamps = !view.show_timestamps,
+        _ => {}
+    }
+    // Scrolling up stops following new output; going back to the bottom
+    // resumes it
+    view.update_following();
+}
+
+/// Open the file of the selected workflow at the default branch, or that of
+/// the selected run at the commit it ran. A run's file opens at the selected
+/// job, or else at the first job that failed.
+fn open_workflow_file(app: &mut AppState, tasks: &mut Tasks) {
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return;
+    };
+    let (path, git_ref, job) = match app.current_panel {
+        Panel::Workflows => {
+            let Some(workflow) = app.workflow_filter() else {
+                return;
+            };
+            (workflow.path.clone(), repo.default_branch.clone(), None)
+        }
+        Panel::Actions | Panel::Details => {
+            let Some(run) = app.selected_run() else {
+                return;
+            };
+            let Some(workflow) = app.workflows.iter().find(|w| w.id == run.workflow_id) else {
+                return;
+            };
+            let jobs = if app.jobs_run_id == Some(run.id) {
+                app.jobs.as_slice()
+            } else {
+                &[]
+            };
+            let selected = app
+                .selected_job
+                .filter(|_| app.current_panel == Panel::Details)
+                .and_then(|i| jobs.get(i));
+            let job = selected.or_else(|| {
+                jobs.iter().find(|job| {
+                    matches!(job.outcome(), "failure" | "timed_out" | "startup_failure")
+                })
+            });
+            (
+                workflow.path.clone(),
+                run.head_sha.clone(),
+                job.map(|job| job.name.clone()),
+            )
+        }
+        Panel::Repositories => return,
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    app.yaml_view = Some(YamlView::new(path.clone(), git_ref.clone(), job));
+    let client = app.client();
+
+    tasks.spawn(TaskKind::WorkflowFile, async move {
+        let result = async {
+            let text = client?
+                .get_file(&owner, &name, &path, &git_ref)
+                .await?;
+            Ok(WorkflowSource::parse(&text))
+        }
+        .await;
+        TaskResult::WorkflowFileLoaded {
+            path,
+            git_ref,
+            result,
+        }
+    });
+}
+
+/// Handle a key while a workflow file is open. Like the log view, it has
+/// fixed keys.
+fn handle_yaml_key(app: &mut AppState, tasks: &mut Tasks, code: KeyCode) {
+    let Some(view) = app.yaml_view.as_mut() else {
+        return;
+    };
+    view.message = None;
+
+    match code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            tasks.cancel(TaskKind::WorkflowFile);
+            app.yaml_view = None;
+        }
+        KeyCode::Up | KeyCode::Char('k') => view.move_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => view.move_cursor(1),
+        KeyCode::PageUp => view.page(-1),
+        KeyCode::PageDown => view.page(1),
+        KeyCode::Home | KeyCode::Char('g') => view.jump_to_start(),
+        KeyCode::End | KeyCode::Char('G') => view.jump_to_end(),
+        KeyCode::Char(']') => view.next_job(true),
+        KeyCode::Char('[') => view.next_job(false),
+        _ => {}
+    }
+}
+
+/// Ask to confirm `action` on the selected run, if it applies to the run.
+/// One action is sent at a time.
+fn open_run_action(app: &mut AppState, tasks: &Tasks, action: RunAction) {
+    if tasks.is_running(TaskKind::RunAction) {
+        return;
+    }
+    let Some(run) = app.selected_run() else {
+        return;
+    };
+    if !action.applies_to(run) {
+        return;
+    }
+    app.popup = Some(Popup::ConfirmRunAction {
+        action,
+        run_id: run.id,
+        run_label: format!("{} #{}", run.name, run.run_number),
+        debug_logging: false,
+    });
+}
+
+/// Send a confirmed `action` for run `run_id`, showing its expected effect
+/// right away.
+fn perform_run_action(
+    app: &mut AppState,
+    tasks: &mut Tasks,
+    action: RunAction,
+    run_id: u64,
+    debug_logging: bool,
+) {
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    app.apply_run_action(run_id, &action);
+    let client = app.client();
+
+    tasks.spawn(TaskKind::RunAction, async move {
+        let result = async {
+            let client = client?;
+            match &action {
+                RunAction::Rerun => {
+                    client
+                        .rerun_workflow_run(&owner, &name, run_id, false, debug_logging)
+                        .await
+                }
+                RunAction::RerunFailed => {
+                    client
+                        .rerun_workflow_run(&owner, &name, run_id, true, debug_logging)
+                        .await
+                }
+                RunAction::RerunJob { job_id, .. } => {
+                    client.rerun_job(&owner, &name, *job_id, debug_logging).await
+                }
+                RunAction::Cancel => client.cancel_workflow_run(&owner, &name, run_id, false).await,
+                RunAction::ForceCancel => {
+                    client.cancel_workflow_run(&owner, &name, run_id, true).await
+                }
+            }
+        }
+        .await;
+        TaskResult::RunActionDone(result)
+    });
+}
+
+/// Open the dispatch popup for the selected repository and list its
+/// workflows.
+fn open_dispatch(app: &mut AppState, tasks: &mut Tasks) {
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    app.popup = Some(Popup::Dispatch(Box::new(Dispatch::new())));
+    let client = app.client();
+
+    tasks.spawn(TaskKind::Dispatch, async move {
+        let result = async {
+            client?
+                .get_repo_workflows(&owner, &name)
+                .await
+        }
+        .await;
+        TaskResult::DispatchWorkflowsLoaded(result)
+    });
+}
+
+fn dispatch_popup(app: &mut AppState) -> Option<&mut Dispatch> {
+    match &mut app.popup {
+        Some(Popup::Dispatch(dispatch)) => Some(dispatch),
+        _ => None,
+    }
+}
+
+fn handle_dispatch_key(app: &mut AppState, tasks: &mut Tasks, code: KeyCode) {
+    let Some(dispatch) = dispatch_popup(app) else {
+        return;
+    };
+    if code == KeyCode::Esc {
+        tasks.cancel(TaskKind::Dispatch);
+        app.popup = None;
+        return;
+    }
+    // Nothing to act on until the step's data arrives
+    if dispatch.loading.is_some() {
+        return;
+    }
+    dispatch.error = None;
+
+    let editing_text = dispatch.editing_text();
+    match code {
+        KeyCode::Up => dispatch.move_selection(-1),
+        KeyCode::Down | KeyCode::Tab => dispatch.move_selection(1),
+        KeyCode::Char('k') if !editing_text => dispatch.move_selection(-1),
+        KeyCode::Char('j') if !editing_text => dispatch.move_selection(1),
+        KeyCode::Left => dispatch.cycle_input(false),
+        KeyCode::Right => dispatch.cycle_input(true),
+        KeyCode::Char(' ') if !editing_text => dispatch.cycle_input(true),
+        KeyCode::Char(c) => dispatch.type_char(c),
+        KeyCode::Backspace => dispatch.backspace(),
+        KeyCode::Enter => advance_dispatch(app, tasks),
+        _ => {}
+    }
+}
+
+/// Take the choice made in the current step of the dispatch popup and fetch
+/// what the next step needs, or dispatch once the inputs are filled in.
+fn advance_dispatch(app: &mut AppState, tasks: &mut Tasks) {
+    let Some(repo) = app.selected_repo.and_then(|i| app.repos.get(i)) else {
+        return;
+    };
+    let owner = repo.owner.clone();
+    let name = repo.repo_name.clone();
+    let after_run_id = app.actions.iter().map(|run| run.id).max().unwrap_or(0);
+    let client = app.client();
+    let Some(dispatch) = dispatch_popup(app) else {
+        return;
+    };
+
+    match dispatch.step {
+        DispatchStep::Workflows { .. } => {
+            if dispatch.choose_workflow().is_none() {
+                return;
+            }
+            dispatch.loading = Some("Loading branches and tags");
+            tasks.spawn(TaskKind::Dispatch, async move {
+                let result = async {
+                    client?
+                        .get_refs(&owner, &name)
+                        .await
+                }
+                .await;
+                TaskResult::DispatchRefsLoaded(result)
+            });
+        }
+        DispatchStep::Refs { .. } => {
+            let (Some(git_ref), Some(workflow)) = (dispatch.choose_ref(), dispatch.workflow.clone())
+            else {
+                return;
+            };
+            dispatch.loading = Some("Reading workflow inputs");
+            tasks.spawn(TaskKind::Dispatch, async move {
+                let result = async {
+                    let client = client?;
+                    let source = client.get_file(&owner, &name, &workflow.path, &git_ref).await?;
+                    let Some(mut inputs) = workflow::dispatch_inputs(&source)? else {
+                        return Ok(None);
+                    };
+                    if inputs
+                        .iter()
+                        .any(|input| matches!(input.kind, InputKind::Environment(_)))
+                    {
+                        // Without access to environments, names are typed in
+                        let environments = client
+                            .get_environments(&owner, &name)
+                            .await
+                            .unwrap_or_default();
+                        for input in &mut inputs {
+                            if let InputKind::Environment(options) = &mut input.kind {
+                                options.clone_from(&environments);
+                            }
                         }
                     }
+                    Ok(Some(inputs))
+                }
+                .await;
+                TaskResult::DispatchInputsLoaded(result)
+            });
+        }
+        DispatchStep::Inputs { .. } => {
+            let (Some(workflow), Some(git_ref)) = (dispatch.workflow.clone(), dispatch.git_ref.clone())
+            else {
+                return;
+            };
+            let Some(inputs) = dispatch.input_values() else {
+                return;
+            };
+            dispatch.loading = Some("Dispatching");
+            let pending = PendingDispatch::new(workflow.id, git_ref.clone(), after_run_id);
+            tasks.spawn(TaskKind::Dispatch, async move {
+                let result = async {
+                    client?
+                        .dispatch_workflow(&owner, &name, workflow.id, &git_ref, inputs)
+                        .await
+                }
+                .await;
+                TaskResult::Dispatched { pending, result }
+            });
+        }
+    }
+}
+
+/// Refresh the session's token shortly before it expires and switch to a
+/// client for the new one. Tasks keep using the current token meanwhile.
+fn refresh_token_if_due(app: &mut AppState, tasks: &mut Tasks) {
+    let Some(session) = &app.session else {
+        return;
+    };
+    if !session.token.needs_refresh()
+        || session.retry_refresh_at.is_some_and(|at| Instant::now() < at)
+        || tasks.is_running(TaskKind::TokenRefresh)
+    {
+        return;
+    }
+    tasks.spawn(TaskKind::TokenRefresh, async {
+        TaskResult::TokenRefreshed(fresh_token().await)
+    });
+}
+
+/// The stored token of the active account, refreshed if about to expire.
+async fn fresh_token() -> Result<TokenData, AppError> {
+    TokenManager::new()?
+        .get_fresh_token()
+        .await?
+        .ok_or_else(|| AppError::AuthFailed("not logged in".to_string()))
+}
+
+/// Show why a background load failed, or clear the last failure once a
+/// load succeeds. Only a rejected token changes the login state.
+fn report_load(app: &mut AppState, result: Result<(), AppError>) {
+    match result {
+        Ok(()) => app.load_error = None,
+        Err(e @ AppError::AuthFailed(_)) => {
+            app.auth_state = AuthState::Error {
+                message: e.user_message(),
+            };
+        }
+        Err(e) => app.load_error = Some(e.user_message()),
+    }
+}
+
+/// Apply the result of a background task to the application state.
+fn handle_task_result(app: &mut AppState, tasks: &mut Tasks, result: TaskResult) {
+    match result {
+        TaskResult::SessionRestored(result) => finish_session_restore(app, tasks, result),
+        TaskResult::LoggedIn {
+            result,
+            from_token_input,
+        } => {
+            if let Some(Popup::BrowserLogin { .. }) = app.popup {
+                app.popup = None;
+            }
+            match result {
+                Ok(token_data) => complete_login(app, tasks, token_data),
+                Err(e) if from_token_input => {
+                    app.auth_state = AuthState::Unauthenticated;
+                    app.popup = Some(Popup::TokenInput {
+                        input: String::new(),
+                        error: Some(e.user_message()),
+                    });
                 }
                 Err(e) => {
-                    app.auth_state = AuthState::Error { 
-                        message: format!("Failed to create GitHub client: {}", e) 
+                    app.auth_state = AuthState::Error {
+                        message: e.user_message(),
                     };
                 }
             }
-            
-            app.set_loading(false);
+        }
+        TaskResult::BrowserLoginStarted(result) => match result {
+            Ok(login) => finish_browser_login(app, tasks, login),
+            Err(e) => {
+                app.auth_state = AuthState::Error {
+                    message: e.user_message(),
+                };
+            }
+        },
+        TaskResult::DeviceCode(result) => match result {
+            Ok(authorization) => {
+                app.popup = Some(Popup::DeviceCode {
+                    authorization: authorization.clone(),
+                    status: "Waiting for authorization...".to_string(),
+                });
+                poll_device_code(tasks, authorization);
+            }
+            Err(e) => {
+                app.auth_state = AuthState::Error {
+                    message: e.user_message(),
+                };
+            }
+        },
+        TaskResult::DevicePolled {
+            authorization,
+            result,
+        } => match result {
+            Ok(DevicePoll::Pending) => poll_device_code(tasks, authorization),
+            Ok(DevicePoll::SlowDown) => {
+                if let Some(Popup::DeviceCode { status, .. }) = &mut app.popup {
+                    *status = format!(
+                        "GitHub asked us to slow down; polling every {}s",
+                        authorization.interval.as_secs()
+                    );
+                }
+                poll_device_code(tasks, authorization);
+            }
+            Ok(DevicePoll::Complete(token_data)) => {
+                app.popup = None;
+                complete_login(app, tasks, *token_data);
+            }
+            Err(e) => {
+                app.popup = None;
+                app.auth_state = AuthState::Error {
+                    message: e.user_message(),
+                };
+            }
+        },
+        TaskResult::TokenRefreshed(result) => {
+            let Some(session) = &mut app.session else {
+                return;
+            };
+            let result = result.and_then(|token_data| {
+                // The active account may have changed in another instance
+                if token_data.account().id() != session.token.account().id() {
+                    return Err(AppError::AuthFailed(
+                        "the active account changed; log in again".to_string(),
+                    ));
+                }
+                Session::new(token_data)
+            });
+            match result {
+                Ok(refreshed) => *session = refreshed,
+                Err(e) => {
+                    session.retry_refresh_at = Some(Instant::now() + TOKEN_REFRESH_RETRY);
+                    report_load(app, Err(e));
+                }
+            }
+        }
+        TaskResult::ReposLoaded(result) => {
+            app.loading_repos = false;
+            match result {
+                Ok(repos) => {
+                    app.repos = repos;
+                    report_load(app, Ok(()));
+                }
+                Err(e) => report_load(app, Err(e)),
+            }
+        }
+        TaskResult::WorkflowsLoaded { repo, result } => {
+            app.loading_workflows = false;
+            let selected = app.selected_repo.and_then(|i| app.repos.get(i));
+            if selected.is_none_or(|selected| selected.full_name() != repo) {
+                return;
+            }
+            match result {
+                Ok(workflows) => {
+                    app.set_workflows(workflows);
+                    report_load(app, Ok(()));
+                }
+                Err(e) => report_load(app, Err(e)),
+            }
+        }
+        TaskResult::WorkflowStateChanged {
+            workflow_id,
+            previous,
+            result,
+        } => {
+            if result.is_err()
+                && let Some(workflow) = app.workflows.iter_mut().find(|w| w.id == workflow_id)
+            {
+                workflow.state = previous;
+            }
+            report_load(app, result);
+        }
+        TaskResult::RunsRefreshed {
+            repo,
+            workflow_id,
+            result,
+        } => {
+            // A further page may still be on its way
+            app.loading_actions = tasks.is_running(TaskKind::RunsPage);
+            let latest = match result {
+                Ok(latest) => latest,
+                Err(e) => {
+                    app.schedule_refresh();
+                    return report_load(app, Err(e));
+                }
+            };
+            report_load(app, Ok(()));
+            let Some(index) = app.repos.iter().position(|r| r.full_name() == repo) else {
+                return;
+            };
+
+            let repo = &mut app.repos[index];
+            repo.last_check = Utc::now();
+            if let Some(run_page) = latest.value {
+                // A filtered page says nothing about the repository's other
+                // workflows
+                if workflow_id.is_none() {
+                    repo.has_actions = Some(run_page.total_count > 0);
+                    repo.last_run = run_page.runs.first().cloned();
+                }
+                app.merge_latest_runs(run_page);
+                app.runs_etag = latest.etag;
+            }
+            app.schedule_refresh();
+            app.select_dispatched_run();
+
+            // Steps of a run in progress change without the run itself
+            // changing, so its jobs are polled along with the runs
+            if let Some(run) = app.selected_run()
+                && !run.is_completed()
+                && app.jobs_run_id == Some(run.id)
+                && !tasks.is_running(TaskKind::Jobs)
+            {
+                fetch_jobs(app, tasks, run.id);
+            }
+            load_jobs_if_needed(app, tasks);
+        }
+        TaskResult::JobsLoaded { run_id, result } => {
+            app.loading_jobs = false;
+            if app.jobs_run_id != Some(run_id) {
+                return;
+            }
+            match result {
+                Ok(jobs) => {
+                    app.set_jobs(run_id, jobs);
+                    report_load(app, Ok(()));
+                }
+                Err(e) => report_load(app, Err(e)),
+            }
+        }
+        TaskResult::LogLoaded {
+            job_id,
+            offset,
+            result,
+        } => {
+            let Some(view) = app
+                .log_view
+                .as_mut()
+                .filter(|view| view.job_id == job_id && view.offset == offset)
+            else {
+                return;
+            };
+            match result {
+                Ok(chunk) => view.append(chunk),
+                // A running job's log may not be available yet; keep trying
+                Err(e) if view.live => view.message = Some(e.user_message()),
+                Err(e) => {
+                    if matches!(view.state, LogState::Loading) {
+                        view.state = LogState::Failed(e.user_message());
+                    } else {
+                        view.message = Some(e.user_message());
+                    }
+                }
+            }
+            if view.live {
+                let interval = config::get().refresh.active_secs.get();
+                view.next_poll = Some(Instant::now() + Duration::from_secs(interval));
+            }
+        }
+        TaskResult::WorkflowFileLoaded {
+            path,
+            git_ref,
+            result,
+        } => {
+            let Some(view) = app
+                .yaml_view
+                .as_mut()
+                .filter(|view| view.path == path && view.git_ref == git_ref)
+            else {
+                return;
+            };
+            match result {
+                Ok(source) => view.load(source),
+                Err(e) => view.state = YamlState::Failed(e.user_message()),
+            }
+        }
+        TaskResult::RunActionDone(result) => {
+            // Drop the ETag so the next poll brings the real state of the run
+            // even if the page looks unchanged
+            app.runs_etag = None;
+            match result {
+                Ok(()) => {
+                    app.schedule_refresh();
+                    report_load(app, Ok(()));
+                }
+                Err(e) => {
+                    app.next_refresh = Some(Instant::now());
+                    report_load(app, Err(e));
+                }
+            }
+        }
+        TaskResult::DispatchWorkflowsLoaded(result) => {
+            let Some(dispatch) = dispatch_popup(app) else {
+                return;
+            };
+            match result {
+                Ok(workflows) => dispatch.set_workflows(workflows),
+                Err(e) => {
+                    dispatch.loading = None;
+                    dispatch.error = Some(e.user_message());
+                }
+            }
+        }
+        TaskResult::DispatchRefsLoaded(result) => {
+            let default_branch = app
+                .selected_repo
+                .and_then(|i| app.repos.get(i))
+                .map(|repo| repo.default_branch.clone())
+                .unwrap_or_default();
+            let Some(dispatch) = dispatch_popup(app) else {
+                return;
+            };
+            match result {
+                Ok(refs) => dispatch.set_refs(refs, &default_branch),
+                Err(e) => {
+                    dispatch.loading = None;
+                    dispatch.error = Some(e.user_message());
+                }
+            }
+        }
+        TaskResult::DispatchInputsLoaded(result) => {
+            let Some(dispatch) = dispatch_popup(app) else {
+                return;
+            };
+            dispatch.loading = None;
+            match result {
+                Ok(Some(inputs)) => dispatch.set_inputs(inputs),
+                Ok(None) => {
+                    dispatch.error = Some(format!(
+                        "{} has no workflow_dispatch trigger on {}",
+                        dispatch.workflow.as_ref().map_or("", |w| w.name.as_str()),
+                        dispatch.git_ref.as_deref().unwrap_or_default()
+                    ));
+                }
+                Err(e) => dispatch.error = Some(e.user_message()),
+            }
+        }
+        TaskResult::Dispatched { pending, result } => {
+            let Some(dispatch) = dispatch_popup(app) else {
+                return;
+            };
+            if let Err(e) = result {
+                dispatch.loading = None;
+                dispatch.error = Some(e.user_message());
+                return;
+            }
+            app.popup = None;
+            app.pending_dispatch = Some(pending);
+            app.runs_etag = None;
+            app.next_refresh = Some(Instant::now());
+        }
+        TaskResult::RunsLoaded { repo, page, result } => {
+            app.loading_actions = false;
+            let run_page = match result {
+                Ok(run_page) => run_page,
+                Err(e) => return report_load(app, Err(e)),
+            };
+            report_load(app, Ok(()));
+            let Some(index) = app.repos.iter().position(|r| r.full_name() == repo) else {
+                return;
+            };
+            app.repos[index].last_check = Utc::now();
+
+            // The next page is only fetched when the user scrolls past the
+            // last run, so carry the selection on to the first new one
+            let at_end = app.selected_action == app.actions.len().checked_sub(1);
+            // Runs that started since the first page was fetched shift later
+            // pages, so some may already be loaded
+            app.note_workflow_runs(&run_page.runs);
+            let new_runs: Vec<_> = run_page
+                .runs
+                .into_iter()
+                .filter(|run| !app.actions.iter().any(|loaded| loaded.id == run.id))
+                .collect();
+            app.actions.extend(new_runs);
+            app.actions_page = page;
+            app.actions_total = run_page.total_count;
+            if at_end {
+                app.move_action_selection(1);
+            }
+            load_jobs_if_needed(app, tasks);
         }
     }
-    Ok(())
 }
 
-async fn load_workflow_actions(app: &mut AppState) -> Result<(), AppError> {
-    if let Some(selected_repo_index) = app.selected_repo {
-        if selected_repo_index < app.repos.len() {
-            let selected_repo = app.repos[selected_repo_index].clone();
-            
-            if let Ok(token_manager) = TokenManager::new() {
-                if let Ok(Some(token_data)) = token_manager.get_token() {
-                    match GitHubClient::new(&token_data.access_token).await {
-                        Ok(_client) => {
-                            // For now, use mock workflow actions
-                            app.set_loading(true);
-                            app.actions.clear();
-                            app.set_loading(false);
-                        }
-                        Err(_) => {
-                            // Error loading workflows
-                            app.actions.clear();
-                        }
-                    }
+fn handle_popup_key(app: &mut AppState, tasks: &mut Tasks, code: KeyCode) {
+    if let Some(Popup::Dispatch(_)) = app.popup {
+        handle_dispatch_key(app, tasks, code);
+        return;
+    }
+
+    if let Some(Popup::TokenInput { input, error }) = &mut app.popup {
+        match code {
+            KeyCode::Char(c) => {
+                input.push(c);
+                *error = None;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter if !input.trim().is_empty() => {
+                let token = input.trim().to_string();
+                app.popup = None;
+                login_with_token(app, tasks, token, true);
+            }
+            KeyCode::Esc => {
+                app.popup = None;
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    if let Some(Popup::DeviceCode { .. } | Popup::BrowserLogin { .. }) = app.popup {
+        if code == KeyCode::Esc {
+            cancel_login(app, tasks);
+        }
+        return;
+    }
+
+    if let Some(Popup::AccountPicker { accounts, selected }) = &mut app.popup {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                *selected = selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                *selected = (*selected + 1).min(accounts.len().saturating_sub(1));
+            }
+            KeyCode::Enter => {
+                if let Some((account, _)) = accounts.get(*selected).cloned() {
+                    app.popup = None;
+                    switch_account(app, tasks, &account);
                 }
             }
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                app.popup = Some(Popup::LoginMethod);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.popup = None;
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    if let Some(Popup::ConfirmRunAction {
+        action,
+        run_id,
+        debug_logging,
+        ..
+    }) = &mut app.popup
+    {
+        match code {
+            KeyCode::Char('d') | KeyCode::Char('D') if action.is_rerun() => {
+                *debug_logging = !*debug_logging;
+            }
+            KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let (action, run_id, debug_logging) = (action.clone(), *run_id, *debug_logging);
+                app.popup = None;
+                perform_run_action(app, tasks, action, run_id, debug_logging);
+            }
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Char('q') => {
+                app.popup = None;
+            }
+            _ => {}
+        }
+        return;
+    }
+
+    if let Some(Popup::LoginMethod) = app.popup {
+        match code {
+            KeyCode::Char('b') | KeyCode::Char('B') => {
+                app.popup = None;
+                login_with_browser(app, tasks);
+            }
+            KeyCode::Char('d') | KeyCode::Char('D') => {
+                app.popup = None;
+                login_with_device_code(app, tasks);
+            }
+            KeyCode::Char('t') | KeyCode::Char('T') => {
+                app.popup = Some(Popup::TokenInput {
+                    input: String::new(),
+                    error: None,
+                });
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.popup = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn login_with_browser(app: &mut AppState, tasks: &mut Tasks) {
+    app.auth_state = AuthState::Authenticating;
+    let host = app.login_host.clone();
+    tasks.spawn(TaskKind::Login, async move {
+        TaskResult::BrowserLoginStarted(start_auth_code_flow(&host))
+    });
+}
+
+/// Wait for the browser login to come back. When the browser couldn't be
+/// opened, its URL is shown in a popup for the user to visit by hand.
+fn finish_browser_login(app: &mut AppState, tasks: &mut Tasks, login: AuthCodeLogin) {
+    if let Some(browser_error) = &login.browser_error {
+        app.popup = Some(Popup::BrowserLogin {
+            authorize_url: login.authorize_url.clone(),
+            browser_error: browser_error.clone(),
+        });
+    }
+    tasks.spawn(TaskKind::Login, async move {
+        TaskResult::LoggedIn {
+            result: finish_auth_code_flow(login).await,
+            from_token_input: false,
+        }
+    });
+}
+
+/// Start the device code flow. Once GitHub issues a code, the verification
+/// URI and user code are shown in a popup while GitHub is polled until the
+/// user approves, denies or presses Esc.
+fn login_with_device_code(app: &mut AppState, tasks: &mut Tasks) {
+    app.auth_state = AuthState::Authenticating;
+    let host = app.login_host.clone();
+    tasks.spawn(TaskKind::Login, async move {
+        TaskResult::DeviceCode(request_device_code(&host).await)
+    });
+}
+
+/// Poll GitHub once for the device code's token after the interval it asked
+/// for.
+fn poll_device_code(tasks: &mut Tasks, mut authorization: DeviceAuthorization) {
+    tasks.spawn(TaskKind::Login, async move {
+        tokio::time::sleep(authorization.interval).await;
+        let result = poll_device_token(&mut authorization).await;
+        TaskResult::DevicePolled {
+            authorization,
+            result,
+        }
+    });
+}
+
+/// Abandon a login in progress.
+fn cancel_login(app: &mut AppState, tasks: &mut Tasks) {
+    tasks.cancel(TaskKind::Login);
+    app.popup = None;
+    app.auth_state = AuthState::Unauthenticated;
+}
+
+fn open_account_picker(app: &mut AppState) {
+    match TokenManager::new().and_then(|tm| tm.list_accounts()) {
+        Ok(accounts) => {
+            let selected = accounts.iter().position(|(_, active)| *active).unwrap_or(0);
+            app.popup = Some(Popup::AccountPicker { accounts, selected });
+        }
+        Err(e) => {
+            app.auth_state = AuthState::Error {
+                message: e.user_message(),
+            };
+        }
+    }
+}
+
+/// Drop everything loaded for the current account, cancelling loads still
+/// in flight.
+fn clear_session(app: &mut AppState, tasks: &mut Tasks) {
+    tasks.cancel(TaskKind::TokenRefresh);
+    tasks.cancel(TaskKind::Repos);
+    tasks.cancel(TaskKind::Workflows);
+    tasks.cancel(TaskKind::Runs);
+    tasks.cancel(TaskKind::RunsPage);
+    tasks.cancel(TaskKind::Logs);
+    tasks.cancel(TaskKind::WorkflowFile);
+    rate_limit::clear();
+    app.session = None;
+    app.log_view = None;
+    app.yaml_view = None;
+    app.token_info = None;
+    app.load_error = None;
+    app.repos.clear();
+    app.loading_repos = false;
+    app.clear_workflows();
+    app.clear_actions();
+    app.selected_repo = None;
+}
+
+/// Make `account` the active account and reload everything for it.
+fn switch_account(app: &mut AppState, tasks: &mut Tasks, account: &Account) {
+    if let Err(e) = TokenManager::new().and_then(|tm| tm.set_active(&account.id())) {
+        app.auth_state = AuthState::Error {
+            message: e.user_message(),
+        };
+        return;
+    }
+
+    clear_session(app, tasks);
+    app.auth_state = AuthState::Authenticating;
+    tasks.spawn(TaskKind::Session, async {
+        TaskResult::SessionRestored(restore_session().await)
+    });
+}
+
+/// Log in with a personal access token, validating it against the API in
+/// the background. Failures reopen the token prompt when the token was typed
+/// into it, and are reported in the header otherwise.
+fn login_with_token(app: &mut AppState, tasks: &mut Tasks, token: String, from_token_input: bool) {
+    app.auth_state = AuthState::Authenticating;
+    let host = app.login_host.clone();
+    tasks.spawn(TaskKind::Login, async move {
+        TaskResult::LoggedIn {
+            result: personal_token_login(&host, token).await,
+            from_token_input,
+        }
+    });
+}
+
+/// Persist a freshly obtained token and load the user's repositories.
+fn complete_login(app: &mut AppState, tasks: &mut Tasks, token_data: TokenData) {
+    // Store token
+    if let Err(e) = TokenManager::new().and_then(|tm| tm.store_token(&token_data)) {
+        app.auth_state = AuthState::Error {
+            message: e.user_message(),
+        };
+        return;
+    }
+
+    // Clear previous data and load repositories
+    clear_session(app, tasks);
+    match Session::new(token_data.clone()) {
+        Ok(session) => app.session = Some(session),
+        Err(e) => {
+            app.auth_state = AuthState::Error {
+                message: e.user_message(),
+            };
+            return;
+        }
+    }
+    app.auth_state = AuthState::Authenticated {
+        username: token_data.login.clone(),
+        host: token_data.host.clone(),
+    };
+    app.token_info = Some(TokenInfo::from(&token_data));
+    load_repositories(app, tasks);
+}
+
+/// Refresh the stored token if it is about to expire and check it is still
+/// accepted by GitHub.
+async fn restore_session() -> Result<(TokenData, TokenInfo), AppError> {
+    let Some(token_data) = TokenManager::new()?.get_fresh_token().await? else {
+        return Err(AppError::AuthFailed("no stored token".to_string()));
+    };
+    let mut token_info =
+        validate_token(&token_data.github_host(), &token_data.access_token).await?;
+    // Expiry of OAuth tokens is only known from the token grant
+    token_info.expires_at = token_info.expires_at.or(token_data.expires_at);
+    Ok((token_data, token_info))
+}
+
+/// Finish restoring the stored session once its token has been validated.
+fn finish_session_restore(
+    app: &mut AppState,
+    tasks: &mut Tasks,
+    result: Result<(TokenData, TokenInfo), AppError>,
+) {
+    let result =
+        result.and_then(|(token_data, token_info)| Ok((Session::new(token_data)?, token_info)));
+    match result {
+        Ok((session, token_info)) => {
+            app.auth_state = AuthState::Authenticated {
+                username: session.token.login.clone(),
+                host: session.token.host.clone(),
+            };
+            app.session = Some(session);
+            app.token_info = Some(token_info);
+            load_repositories(app, tasks);
+        }
+        // GitHub rejected the token: it was revoked or has expired
+        Err(AppError::AuthFailed(_)) => {
+            if let Ok(token_manager) = TokenManager::new() {
+                let _ = token_manager.clear_token();
+            }
+            app.auth_state = AuthState::Error {
+                message: "Your saved login has expired or was revoked - press 'L' to log in again"
+                    .to_string(),
+            };
+        }
+        Err(e) => {
+            app.auth_state = AuthState::Error {
+                message: format!("Could not restore session: {}", e.user_message()),
+            };
         }
     }
-    Ok(())
 }
 
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut AppState,
+    events: &mut EventLoop,
+    tasks: &mut Tasks,
 ) -> Result<()> {
     loop {
-        // Draw UI
         terminal.draw(|f| ui::render(f, app))?;
 
-        // Handle events
-        if let Ok(Event::Key(key)) = crossterm::event::read() {
-            match key.code {
-                KeyCode::Up | KeyCode::Char('k') => {
+        let key = match events.next().await {
+            Event::Tick => {
+                app.tick();
+                refresh_token_if_due(app, tasks);
+                refresh_workflow_runs_if_due(app, tasks);
+                refresh_log_if_due(app, tasks);
+                continue;
+            }
+            Event::Task { id, result } => {
+                if tasks.finished(result.kind(), id) {
+                    handle_task_result(app, tasks, *result);
+                }
+                continue;
+            }
+            Event::Terminal(TerminalEvent::Key(key)) if key.kind == KeyEventKind::Press => key,
+            // Resizes only need the redraw at the top of the loop
+            Event::Terminal(_) => continue,
+        };
+
+        if app.popup.is_some() {
+            handle_popup_key(app, tasks, key.code);
+            continue;
+        }
+        if app.log_view.is_some() {
+            handle_log_key(app, tasks, key.code);
+            continue;
+        }
+        if app.yaml_view.is_some() {
+            handle_yaml_key(app, tasks, key.code);
+            continue;
+        }
+
+        // Esc abandons a browser login waiting for its callback
+        if key.code == KeyCode::Esc && tasks.is_running(TaskKind::Login) {
+            cancel_login(app, tasks);
+            continue;
+        }
+
+        let Some(action) = config::get().keybindings.action(&key) else {
+            continue;
+        };
+        match action {
+            Action::Up => {
+                if app.current_panel == Panel::Details {
+                    app.move_job_selection(-1);
+                } else if app.current_panel == Panel::Actions {
+                    app.move_action_selection(-1);
+                } else if app.current_panel == Panel::Workflows {
+                    if app.move_workflow_selection(-1) {
+                        load_workflow_actions(app, tasks);
+                    }
+                } else {
                     let old_selected = app.selected_repo;
                     app.move_selection(-1);
-                    
+
                     // Load workflow actions if repository selection changed
                     if old_selected != app.selected_repo {
-                        let _ = load_workflow_actions(app).await;
+                        load_workflows(app, tasks);
+                        load_workflow_actions(app, tasks);
                     }
                 }
-                KeyCode::Down | KeyCode::Char('j') => {
+            }
+            Action::Down => {
+                if app.current_panel == Panel::Details {
+                    app.move_job_selection(1);
+                } else if app.current_panel == Panel::Actions {
+                    // Fetch the next page once the user scrolls past the last run
+                    if app.move_action_selection(1) {
+                        load_more_workflow_actions(app, tasks);
+                    }
+                } else if app.current_panel == Panel::Workflows {
+                    if app.move_workflow_selection(1) {
+                        load_workflow_actions(app, tasks);
+                    }
+                } else {
                     let old_selected = app.selected_repo;
                     app.move_selection(1);
-                    
+
                     // Load workflow actions if repository selection changed
                     if old_selected != app.selected_repo {
-                        let _ = load_workflow_actions(app).await;
+                        load_workflows(app, tasks);
+                        load_workflow_actions(app, tasks);
                     }
                 }
-                KeyCode::Left | KeyCode::Char('h') => {
+            }
+            // In the Details panel, left and right step into and out of
+            // the selected job's steps before moving between panels
+            Action::Left => {
+                if !(app.current_panel == Panel::Details && app.close_job_steps()) {
                     app.switch_panel(-1);
                 }
-                KeyCode::Right => {
+            }
+            Action::Right => {
+                if !(app.current_panel == Panel::Details && app.open_job_steps()) {
                     app.switch_panel(1);
                 }
-                KeyCode::Tab => {
-                    app.switch_panel(1);
+            }
+            Action::NextPanel => {
+                app.switch_panel(1);
+            }
+            Action::Logs => {
+                if app.current_panel == Panel::Details {
+                    open_job_log(app, tasks);
                 }
-                KeyCode::Char('l') | KeyCode::Char('L') => {
-                    // Handle login/logout based on current auth state
-                    match &app.auth_state {
-                        app::AuthState::Unauthenticated => {
-                            // Set to authenticating state for UI feedback
-                            app.auth_state = app::AuthState::Authenticating;
-                            
-                            // Attempt real authentication
-                            match auth_code_flow().await {
-                                Ok(auth_result) => {
-                                    // Store token
-                                    let _token_manager = TokenManager::new().and_then(|tm| {
-                                        tm.store_token(&auth_result.access_token)
-                                    });
-                                    
-                                    // Update auth state with real username
-                                    app.auth_state = app::AuthState::Authenticated { 
-                                        username: auth_result.username 
-                                    };
-                                    
-                                    // Clear previous data and load repositories
-                                    app.repos.clear();
-                                    app.actions.clear();
-                                    app.selected_repo = None;
-                                    app.selected_action = None;
-                                    
-                                    // Load user repositories
-                                    if let Err(e) = load_repositories(app).await {
-                                        app.auth_state = AuthState::Error { 
-                                            message: format!("Failed to load repositories: {}", e) 
-                                        };
-                                    }
-                                }
-                                Err(e) => {
-                                    app.auth_state = app::AuthState::Error { 
-                                        message: format!("Login failed: {}", e) 
-                                    };
-                                }
-                            }
-                        }
-                        app::AuthState::Authenticated { .. } => {
-                            // Logout
-                            app.auth_state = app::AuthState::Unauthenticated;
-                            app.repos.clear();
-                            app.actions.clear();
-                            app.selected_repo = None;
-                            app.selected_action = None;
-                            
-                            // Clear stored token
-                            if let Ok(token_manager) = TokenManager::new() {
-                                let _ = token_manager.clear_token();
-                            }
+            }
+            Action::WorkflowFile => {
+                open_workflow_file(app, tasks);
+            }
+            // Run actions work on the selected run from the Action Runs and
+            // Details panels; in Details, re-run takes the selected job
+            Action::Rerun | Action::RerunFailed | Action::Cancel | Action::ForceCancel
+                if matches!(app.current_panel, Panel::Actions | Panel::Details) =>
+            {
+                let job = app
+                    .selected_job
+                    .and_then(|i| app.jobs.get(i))
+                    .filter(|_| app.current_panel == Panel::Details);
+                let run_action = match (action, job) {
+                    (Action::Rerun, Some(job)) => RunAction::RerunJob {
+                        job_id: job.id,
+                        job_name: job.name.clone(),
+                    },
+                    (Action::Rerun, None) => RunAction::Rerun,
+                    (Action::RerunFailed, _) => RunAction::RerunFailed,
+                    (Action::Cancel, _) => RunAction::Cancel,
+                    _ => RunAction::ForceCancel,
+                };
+                open_run_action(app, tasks, run_action);
+            }
+            Action::Rerun | Action::RerunFailed | Action::Cancel | Action::ForceCancel => {}
+            Action::Dispatch => {
+                open_dispatch(app, tasks);
+            }
+            Action::ToggleWorkflow => {
+                if app.current_panel == Panel::Workflows {
+                    toggle_workflow(app, tasks);
+                }
+            }
+            Action::Login => {
+                // Handle login/logout based on current auth state
+                match &app.auth_state {
+                    app::AuthState::Unauthenticated | app::AuthState::Error { .. } => {
+                        app.popup = Some(Popup::LoginMethod);
+                    }
+                    app::AuthState::Authenticated { .. } => {
+                        // Logout
+                        tasks.cancel_all();
+                        clear_session(app, tasks);
+                        app.auth_state = app::AuthState::Unauthenticated;
+
+                        // Clear stored token
+                        if let Ok(token_manager) = TokenManager::new() {
+                            let _ = token_manager.clear_token();
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
-                KeyCode::Char('q') | KeyCode::Char('Q') => {
-                    return Ok(());
-                }
-                _ => {}
+            }
+            Action::Accounts => {
+                open_account_picker(app);
+            }
+            Action::Quit => {
+                return Ok(());
             }
         }
+        load_jobs_if_needed(app, tasks);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,202 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::app::dispatch::{Dispatch, DispatchStep};
+use crate::config::Theme;
+use crate::workflow::{DispatchInput, InputKind};
+use super::{centered_rect, theme};
+
+/// Draw the popup that starts a `workflow_dispatch` run: the workflow list,
+/// then the ref picker, then the input form.
+pub fn render_dispatch(f: &mut Frame, dispatch: &Dispatch, spinner: char) {
+    let theme = theme();
+    let mut title = "Run Workflow".to_string();
+    if let Some(workflow) = &dispatch.workflow {
+        title.push_str(&format!(" · {}", workflow.name));
+    }
+    if let Some(git_ref) = &dispatch.git_ref
+        && matches!(dispatch.step, DispatchStep::Inputs { .. })
+    {
+        title.push_str(&format!(" @ {}", git_ref));
+    }
+
+    let area = centered_rect(70, 70, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(theme.accent));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),    // Step
+            Constraint::Length(1), // Status
+            Constraint::Length(1), // Keys
+        ])
+        .split(inner);
+
+    match &dispatch.step {
+        DispatchStep::Workflows {
+            workflows,
+            selected,
+        } => {
+            let items: Vec<ListItem> = workflows
+                .iter()
+                .map(|workflow| {
+                    ListItem::new(Line::from(vec![
+                        Span::raw(workflow.name.clone()),
+                        Span::styled(
+                            format!("  {}", workflow.path),
+                            Style::default().fg(theme.muted),
+                        ),
+                    ]))
+                })
+                .collect();
+            render_list(f, chunks[0], items, *selected, &theme);
+        }
+        DispatchStep::Refs {
+            filter, selected, ..
+        } => {
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(2), Constraint::Min(0)])
+                .split(chunks[0]);
+            let prompt = Paragraph::new(Line::from(vec![
+                Span::styled("Ref: ", Style::default().fg(theme.accent)),
+                Span::raw(filter.clone()),
+                Span::styled("█", Style::default().fg(theme.accent)),
+            ]));
+            f.render_widget(prompt, parts[0]);
+
+            let items: Vec<ListItem> = dispatch
+                .matching_refs()
+                .into_iter()
+                .map(|name| ListItem::new(name.to_string()))
+                .collect();
+            render_list(f, parts[1], items, *selected, &theme);
+        }
+        DispatchStep::Inputs { inputs, selected } => {
+            if inputs.is_empty() {
+                let empty = Paragraph::new("This workflow takes no inputs.")
+                    .style(Style::default().fg(theme.muted));
+                f.render_widget(empty, chunks[0]);
+            } else {
+                let mut lines = Vec::new();
+                let mut selected_row = 0;
+                for (i, input) in inputs.iter().enumerate() {
+                    if i == *selected {
+                        selected_row = lines.len();
+                    }
+                    lines.extend(input_lines(input, i == *selected, &theme));
+                }
+                // Keep the selected input in view
+                let scroll = selected_row.saturating_sub(chunks[0].height as usize / 2);
+                let form = Paragraph::new(lines)
+                    .wrap(Wrap { trim: false })
+                    .scroll((scroll as u16, 0));
+                f.render_widget(form, chunks[0]);
+            }
+        }
+    }
+
+    let status = if let Some(loading) = dispatch.loading {
+        Line::styled(
+            format!("{} {}...", spinner, loading),
+            Style::default().fg(theme.busy),
+        )
+    } else if let Some(error) = &dispatch.error {
+        Line::styled(error.clone(), Style::default().fg(theme.error))
+    } else {
+        Line::from("")
+    };
+    f.render_widget(Paragraph::new(status), chunks[1]);
+
+    let help = match dispatch.step {
+        DispatchStep::Workflows { .. } => "[↑↓] Select [Enter] Choose [Esc] Cancel",
+        DispatchStep::Refs { .. } => "Type to filter [↑↓] Select [Enter] Choose [Esc] Cancel",
+        DispatchStep::Inputs { .. } => {
+            "[↑↓/Tab] Field [←→/Space] Change option [Enter] Run workflow [Esc] Cancel"
+        }
+    };
+    f.render_widget(
+        Paragraph::new(Line::styled(help, Style::default().fg(theme.muted))),
+        chunks[2],
+    );
+}
+
+fn render_list(f: &mut Frame, area: Rect, items: Vec<ListItem>, selected: usize, theme: &Theme) {
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(theme.on_accent)
+            .bg(theme.accent)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ListState::default();
+    state.select(Some(selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// An input as a line of name and value, followed by its description or the
+/// reason its value was refused.
+fn input_lines(input: &DispatchInput, selected: bool, theme: &Theme) -> Vec<Line<'static>> {
+    let name_style = if selected {
+        Style::default()
+            .fg(theme.on_accent)
+            .bg(theme.accent)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().add_modifier(Modifier::BOLD)
+    };
+    let mut spans = vec![Span::styled(input.name.clone(), name_style)];
+    if input.required {
+        spans.push(Span::styled("*", Style::default().fg(theme.error)));
+    }
+    spans.push(Span::raw(": "));
+
+    let value = if input.is_text() {
+        let mut value = input.value.clone();
+        if selected {
+            value.push('█');
+        }
+        value
+    } else {
+        format!("◂ {} ▸", input.value)
+    };
+    spans.push(Span::styled(value, Style::default().fg(theme.accent)));
+    let kind = match &input.kind {
+        InputKind::String => "",
+        InputKind::Boolean => "boolean",
+        InputKind::Number => "number",
+        InputKind::Choice(_) => "choice",
+        InputKind::Environment(_) => "environment",
+    };
+    if !kind.is_empty() {
+        spans.push(Span::styled(
+            format!("  ({})", kind),
+            Style::default().fg(theme.dim),
+        ));
+    }
+
+    let mut lines = vec![Line::from(spans)];
+    if let Some(error) = &input.error {
+        lines.push(Line::styled(
+            format!("  {}", error),
+            Style::default().fg(theme.error),
+        ));
+    } else if let Some(description) = &input.description {
+        lines.push(Line::styled(
+            format!("  {}", description),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    lines.push(Line::from(""));
+    lines
+}
@@ -0,0 +1,300 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::app::log_view::{LineKind, LogLine, LogState, LogView};
+use crate::config::Theme;
+use super::theme;
+
+/// Draw the log of a job over the main panels. Only the rows on screen are
+/// styled, so long logs cost no more to draw than short ones.
+pub fn render_log_view(f: &mut Frame, area: Rect, view: &LogView, spinner: char) {
+    let theme = theme();
+    let position = match view.cursor_line() {
+        Some(line) => format!("line {}/{}", line + 1, view.line_count()),
+        None => String::new(),
+    };
+    let mut title = vec![Span::raw(format!("Log · {}", view.job_name))];
+    if view.live {
+        let tail = if view.following {
+            format!(" {} following", spinner)
+        } else {
+            " ● live · [G] follow".to_string()
+        };
+        title.push(Span::styled(tail, Style::default().fg(theme.busy)));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Line::from(title))
+        .title_top(Line::styled(position, Style::default().fg(theme.muted)).right_aligned())
+        .border_style(Style::default().fg(theme.accent));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    match &view.state {
+        LogState::Loading => {
+            let loading = Paragraph::new(format!("{} Downloading log...", spinner))
+                .style(Style::default().fg(theme.busy));
+            f.render_widget(loading, inner);
+        }
+        LogState::Failed(message) => {
+            let failed = Paragraph::new(message.as_str()).style(Style::default().fg(theme.error));
+            f.render_widget(failed, inner);
+        }
+        LogState::Loaded(_) => render_lines(f, inner, view, &theme),
+    }
+}
+
+fn render_lines(f: &mut Frame, area: Rect, view: &LogView, theme: &Theme) {
+    let height = area.height as usize;
+    view.height.set(height);
+
+    // Scroll only as far as needed to keep the cursor on screen
+    let mut scroll = view.scroll.get();
+    if view.cursor < scroll {
+        scroll = view.cursor;
+    } else if height > 0 && view.cursor >= scroll + height {
+        scroll = view.cursor + 1 - height;
+    }
+    scroll = scroll.min(view.visible_len().saturating_sub(height));
+    view.scroll.set(scroll);
+
+    let number_width = view.line_count().to_string().len();
+    let query = view.query.as_deref().map(str::to_lowercase);
+    let lines: Vec<Line> = view
+        .rows(scroll..scroll + height)
+        .enumerate()
+        .map(|(row, (index, line, folded))| {
+            let fold = match folded {
+                Some(true) => "▸ ",
+                Some(false) => "▾ ",
+                None => "  ",
+            };
+            let mut spans = vec![
+                Span::styled(
+                    format!("{:>width$} ", index + 1, width = number_width),
+                    Style::default().fg(theme.dim),
+                ),
+                Span::styled(fold, Style::default().fg(theme.accent)),
+            ];
+            if view.show_timestamps
+                && let Some(timestamp) = line.timestamp()
+            {
+                spans.push(Span::styled(
+                    format!("{} ", timestamp),
+                    Style::default().fg(theme.dim),
+                ));
+            }
+            spans.extend(content_spans(line, query.as_deref(), theme));
+
+            let mut rendered = Line::from(spans);
+            if scroll + row == view.cursor {
+                rendered = rendered.style(Style::default().bg(theme.dim));
+            }
+            rendered
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+/// The content of a line styled by its ANSI escapes and workflow command,
+/// with matches of the search highlighted.
+fn content_spans(line: &LogLine, query: Option<&str>, theme: &Theme) -> Vec<Span<'static>> {
+    let base = match line.kind {
+        LineKind::GroupStart => Style::default().add_modifier(Modifier::BOLD),
+        LineKind::Error => Style::default().fg(theme.error),
+        LineKind::Warning => Style::default().fg(theme.warning),
+        LineKind::Notice | LineKind::Command => Style::default().fg(theme.info),
+        LineKind::Debug => Style::default().fg(theme.muted),
+        LineKind::Plain | LineKind::GroupEnd => Style::default(),
+    };
+    let prefix = match line.kind {
+        LineKind::Error => "Error: ",
+        LineKind::Warning => "Warning: ",
+        LineKind::Notice => "Notice: ",
+        _ => "",
+    };
+
+    let mut segments = ansi_segments(line.content(), base);
+    if !prefix.is_empty() {
+        segments.insert(0, (prefix.to_string(), base.add_modifier(Modifier::BOLD)));
+    }
+    if let Some(query) = query.filter(|query| !query.is_empty()) {
+        let highlight = Style::default().fg(theme.on_accent).bg(theme.warning);
+        segments = highlight_matches(segments, query, highlight);
+    }
+    segments
+        .into_iter()
+        .map(|(text, style)| Span::styled(text, style))
+        .collect()
+}
+
+/// Split `text` into runs of text with the style its SGR escapes give it on
+/// top of `base`. Other escape sequences are dropped.
+fn ansi_segments(text: &str, base: Style) -> Vec<(String, Style)> {
+    let mut segments = Vec::new();
+    let mut style = base;
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            // Tabs would throw off the terminal's columns
+            if c == '\t' {
+                current.push_str("    ");
+            } else {
+                current.push(c);
+            }
+            continue;
+        }
+        if chars.next_if_eq(&'[').is_none() {
+            chars.next();
+            continue;
+        }
+        let mut params = String::new();
+        let mut terminator = None;
+        for c in chars.by_ref() {
+            if ('@'..='~').contains(&c) {
+                terminator = Some(c);
+                break;
+            }
+            params.push(c);
+        }
+        if terminator != Some('m') {
+            continue;
+        }
+
+        if !current.is_empty() {
+            segments.push((std::mem::take(&mut current), style));
+        }
+        style = apply_sgr(style, base, &params);
+    }
+    if !current.is_empty() {
+        segments.push((current, style));
+    }
+    segments
+}
+
+/// Apply the parameters of an SGR escape, such as `1;31`, to `style`.
+fn apply_sgr(mut style: Style, base: Style, params: &str) -> Style {
+    let codes: Vec<u16> = params
+        .split(';')
+        .map(|code| code.parse().unwrap_or(0))
+        .collect();
+    let mut codes = codes.iter().copied();
+
+    while let Some(code) = codes.next() {
+        style = match code {
+            0 => base,
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            7 => style.add_modifier(Modifier::REVERSED),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(Color::Indexed((code - 30) as u8)),
+            90..=97 => style.fg(Color::Indexed((code - 90 + 8) as u8)),
+            40..=47 => style.bg(Color::Indexed((code - 40) as u8)),
+            100..=107 => style.bg(Color::Indexed((code - 100 + 8) as u8)),
+            39 => match base.fg {
+                Some(fg) => style.fg(fg),
+                None => style.fg(Color::Reset),
+            },
+            49 => style.bg(Color::Reset),
+            38 | 48 => match extended_color(&mut codes) {
+                Some(color) if code == 38 => style.fg(color),
+                Some(color) => style.bg(color),
+                None => style,
+            },
+            _ => style,
+        };
+    }
+    style
+}
+
+/// The colour of a `38;5;n` or `38;2;r;g;b` sequence, after the 38 or 48.
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(Color::Indexed(codes.next()? as u8)),
+        2 => Some(Color::Rgb(
+            codes.next()? as u8,
+            codes.next()? as u8,
+            codes.next()? as u8,
+        )),
+        _ => None,
+    }
+}
+
+/// Restyle the parts of `segments` that match `query`, ignoring case.
+fn highlight_matches(
+    segments: Vec<(String, Style)>,
+    query: &str,
+    highlight: Style,
+) -> Vec<(String, Style)> {
+    let plain: String = segments.iter().map(|(text, _)| text.as_str()).collect();
+    let lower = plain.to_lowercase();
+    // Lowercasing can change byte lengths; skip highlighting rather than
+    // slice at the wrong places
+    if lower.len() != plain.len() {
+        return segments;
+    }
+    let matches: Vec<(usize, usize)> = lower
+        .match_indices(query)
+        .map(|(start, found)| (start, start + found.len()))
+        .collect();
+    if matches.is_empty() {
+        return segments;
+    }
+
+    let mut result = Vec::new();
+    let mut offset = 0;
+    for (text, style) in segments {
+        let end = offset + text.len();
+        let mut cursor = offset;
+        for &(start, stop) in &matches {
+            let (start, stop) = (start.max(cursor), stop.min(end));
+            if start >= stop {
+                continue;
+            }
+            if start > cursor {
+                result.push((plain[cursor..start].to_string(), style));
+            }
+            result.push((plain[start..stop].to_string(), style.patch(highlight)));
+            cursor = stop;
+        }
+        if cursor < end {
+            result.push((plain[cursor..end].to_string(), style));
+        }
+        offset = end;
+    }
+    result
+}
+
+/// The footer line of the log view: the search being typed, feedback from
+/// the last command, or the keys.
+pub fn status_line(view: &LogView, theme: &Theme) -> Line<'static> {
+    if let Some(input) = &view.input {
+        return Line::from(vec![
+            Span::styled("/", Style::default().fg(theme.accent)),
+            Span::raw(input.clone()),
+            Span::styled("█", Style::default().fg(theme.accent)),
+        ]);
+    }
+    if let Some(message) = &view.message {
+        return Line::styled(message.clone(), Style::default().fg(theme.warning));
+    }
+    Line::styled(
+        "[↑↓] Scroll [PgUp/PgDn] Page [g/G] Top/Bottom [Enter] Fold [z] Fold all [e/E] Next/Prev error [/] Search [n/N] Next/Prev match [t] Timestamps [Esc] Close",
+        Style::default().fg(theme.muted),
+    )
+}
@@ -1,13 +1,24 @@
+mod dispatch;
+mod log_view;
+mod yaml_view;
+
+use std::time::Instant;
+
+use chrono::Utc;
+
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Margin, Rect},
-    style::{Color, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
-use crate::app::{AppState, AuthState, Panel};
-use crate::github::{RepoWithActions, WorkflowSummary};
+use crate::app::{AppState, AuthState, Panel, Popup};
+use crate::auth::TokenInfo;
+use crate::config::{self, APP_NAME, Action, Theme};
+use crate::github::{WorkflowRun, rate_limit};
+use crate::utils::format::{format_duration, format_wait};
 
 pub fn render(f: &mut Frame, app: &AppState) {
     let chunks = Layout::default()
@@ -22,53 +33,116 @@ pub fn render(f: &mut Frame, app: &AppState) {
     // Render header
     render_header(f, chunks[0], app);
 
-    // Render main content
-    render_main_content(f, chunks[1], app);
+    // Render main content, or the log of a job or a workflow file over it
+    if let Some(view) = &app.log_view {
+        log_view::render_log_view(f, chunks[1], view, app.spinner());
+    } else if let Some(view) = &app.yaml_view {
+        yaml_view::render_yaml_view(f, chunks[1], view, app.spinner());
+    } else {
+        render_main_content(f, chunks[1], app);
+    }
 
     // Render footer
     render_footer(f, chunks[2], app);
 
-    // Render loading overlay if needed
-    if app.loading {
-        render_loading_overlay(f);
+    match &app.popup {
+        Some(Popup::Dispatch(dispatch)) => dispatch::render_dispatch(f, dispatch, app.spinner()),
+        Some(popup) => render_popup(f, popup),
+        None => {}
     }
 }
 
+fn theme() -> Theme {
+    config::get().ui.theme()
+}
+
 fn render_header(f: &mut Frame, area: Rect, app: &AppState) {
+    let theme = theme();
     let auth_status = match &app.auth_state {
-        AuthState::Authenticated { username } => Line::from(vec![
-            Span::styled("🔒 ", Style::default().fg(Color::Green)),
-            Span::styled("Authenticated as ", Style::default()),
-            Span::styled(username, Style::default().fg(Color::Cyan)),
-        ]),
+        AuthState::Authenticated { username, host } => {
+            let mut spans = vec![
+                Span::styled("🔒 ", Style::default().fg(theme.success)),
+                Span::styled("Authenticated as ", Style::default()),
+                Span::styled(username, Style::default().fg(theme.info)),
+                Span::styled(format!(" @ {}", host), Style::default().fg(theme.muted)),
+            ];
+            if let Some(info) = &app.token_info {
+                spans.extend(token_info_spans(info));
+            }
+            if let Some(error) = &app.load_error {
+                spans.push(Span::styled(
+                    format!(" · ❌ {}", error),
+                    Style::default().fg(theme.error),
+                ));
+            }
+            Line::from(spans)
+        }
         AuthState::Unauthenticated => Line::from(vec![
-            Span::styled("🔓 ", Style::default().fg(Color::Yellow)),
+            Span::styled("🔓 ", Style::default().fg(theme.warning)),
             Span::styled(
-                "Not authenticated - Press 'L' to login",
-                Style::default().fg(Color::Yellow),
+                format!(
+                    "Not authenticated - Press '{}' to login",
+                    config::get().keybindings.label(Action::Login)
+                ),
+                Style::default().fg(theme.warning),
             ),
         ]),
         AuthState::Authenticating => Line::from(vec![
-            Span::styled("🔄 ", Style::default().fg(Color::Blue)),
-            Span::styled("Authenticating...", Style::default().fg(Color::Blue)),
+            Span::styled(format!("{} ", app.spinner()), Style::default().fg(theme.busy)),
+            Span::styled("Authenticating...", Style::default().fg(theme.busy)),
         ]),
         AuthState::Error { message } => Line::from(vec![
-            Span::styled("❌ ", Style::default().fg(Color::Red)),
-            Span::styled(message, Style::default().fg(Color::Red)),
+            Span::styled("❌ ", Style::default().fg(theme.error)),
+            Span::styled(message, Style::default().fg(theme.error)),
         ]),
     };
 
-    let header_block = Block::default()
+    let mut header_block = Block::default()
         .borders(Borders::ALL)
-        .title("OXA - GitHub Actions TUI");
+        .title(APP_NAME);
+    if let Some(updated) = last_updated(app) {
+        header_block = header_block
+            .title_top(Line::styled(updated, Style::default().fg(theme.muted)).right_aligned());
+    }
 
     let header = Paragraph::new(auth_status)
         .block(header_block)
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(theme.text));
 
     f.render_widget(header, area);
 }
 
+/// How long ago the selected repository's runs were last fetched, e.g.
+/// `updated 12s ago`.
+fn last_updated(app: &AppState) -> Option<String> {
+    let repo = app.repos.get(app.selected_repo?)?;
+    // `last_check` only reflects the runs once they have been fetched
+    repo.has_actions?;
+    let secs = (Utc::now() - repo.last_check).num_seconds().max(0);
+    Some(match secs {
+        0..60 => format!("updated {}s ago", secs),
+        60..3600 => format!("updated {}m ago", secs / 60),
+        _ => format!("updated {}h ago", secs / 3600),
+    })
+}
+
+/// Describe a personal access token's scopes and expiry for the header.
+fn token_info_spans(info: &TokenInfo) -> Vec<Span<'static>> {
+    let theme = theme();
+    let mut spans = vec![Span::styled(
+        format!(" ({})", info.describe()),
+        Style::default().fg(theme.muted),
+    )];
+    let missing = info.missing_scopes();
+    if !missing.is_empty() {
+        spans.push(Span::styled(
+            format!(" missing: {}", missing.join(", ")),
+            Style::default().fg(theme.warning),
+        ));
+    }
+    spans
+}
+
 fn render_main_content(f: &mut Frame, area: Rect, app: &AppState) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -79,26 +153,42 @@ fn render_main_content(f: &mut Frame, area: Rect, app: &AppState) {
         ])
         .split(area);
 
-    render_repos_panel(f, chunks[0], app);
+    let left = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[0]);
+
+    render_repos_panel(f, left[0], app);
+    render_workflows_panel(f, left[1], app);
     render_actions_panel(f, chunks[1], app);
     render_details_panel(f, chunks[2], app);
 }
 
 fn render_repos_panel(f: &mut Frame, area: Rect, app: &AppState) {
+    let theme = theme();
+    let title = if app.loading_repos {
+        format!("Repositories {}", app.spinner())
+    } else {
+        "Repositories".to_string()
+    };
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Repositories")
+        .title(title)
         .border_style(if app.current_panel == Panel::Repositories {
-            Color::Yellow
+            theme.accent
         } else {
-            Color::Gray
+            theme.muted
         });
 
     f.render_widget(block, area);
 
     if app.repos.is_empty() {
-        let no_repos =
-            Paragraph::new("No repositories found").style(Style::default().fg(Color::Gray));
+        let message = if app.loading_repos {
+            "Loading repositories..."
+        } else {
+            "No repositories found"
+        };
+        let no_repos = Paragraph::new(message).style(Style::default().fg(theme.muted));
         f.render_widget(no_repos, area.inner(Margin::new(1, 1)));
         return;
     }
@@ -110,26 +200,34 @@ fn render_repos_panel(f: &mut Frame, area: Rect, app: &AppState) {
         .map(|(i, repo)| {
             let is_selected = Some(i) == app.selected_repo;
             let style = if is_selected {
-                Style::default().fg(Color::Black).bg(Color::Yellow)
+                Style::default().fg(theme.on_accent).bg(theme.accent)
             } else {
                 Style::default()
             };
 
-            let status_indicator = if repo.has_actions {
-                let conclusion = repo
+            let status_indicator = match repo.has_actions {
+                Some(true) => repo
                     .last_run
                     .as_ref()
                     .and_then(|r| r.conclusion.as_deref())
-                    .unwrap_or("○");
-                format!("{} ({})", conclusion, "1")
+                    .unwrap_or("○")
+                    .to_string(),
+                Some(false) => "No Actions".to_string(),
+                None => String::new(),
+            };
+
+            let flags = repo.flags();
+            let flags = if flags.is_empty() {
+                String::new()
             } else {
-                "No Actions".to_string()
+                format!(" [{}]", flags.join(", "))
             };
 
             let content = Line::from(vec![
-                Span::styled(&repo.repo_name, style),
+                Span::styled(repo.full_name(), style),
+                Span::styled(flags, Style::default().fg(theme.dim)),
                 Span::raw(" "),
-                Span::styled(status_indicator, Style::default().fg(Color::Gray)),
+                Span::styled(status_indicator, Style::default().fg(theme.muted)),
             ]);
 
             ListItem::new(content)
@@ -142,26 +240,137 @@ fn render_repos_panel(f: &mut Frame, area: Rect, app: &AppState) {
     }
 
     let list =
-        List::new(items).highlight_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+        List::new(items).highlight_style(Style::default().fg(theme.on_accent).bg(theme.accent));
+
+    f.render_stateful_widget(list, area.inner(Margin::new(1, 1)), &mut list_state);
+}
+
+fn render_workflows_panel(f: &mut Frame, area: Rect, app: &AppState) {
+    let theme = theme();
+    let title = if app.loading_workflows {
+        format!("Workflows {}", app.spinner())
+    } else {
+        "Workflows".to_string()
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(if app.current_panel == Panel::Workflows {
+            theme.accent
+        } else {
+            theme.muted
+        });
+
+    f.render_widget(block, area);
+
+    if app.workflows.is_empty() {
+        let message = if app.selected_repo.is_none() {
+            "No repository selected"
+        } else if app.loading_workflows {
+            "Loading workflows..."
+        } else {
+            "No workflows"
+        };
+        let no_workflows = Paragraph::new(message).style(Style::default().fg(theme.muted));
+        f.render_widget(no_workflows, area.inner(Margin::new(1, 1)));
+        return;
+    }
+
+    // The first row lifts the filter on the runs
+    let mut items = vec![ListItem::new(Span::styled(
+        "All workflows",
+        Style::default().fg(theme.muted),
+    ))];
+    items.extend(app.workflows.iter().map(|workflow| {
+        let last_run = app.workflow_last_runs.get(&workflow.id);
+        let mut spans = vec![
+            match last_run {
+                Some(run) => outcome_icon(run.outcome(), &theme),
+                None => Span::raw(" "),
+            },
+            Span::raw(" "),
+            Span::raw(workflow.name.clone()),
+            Span::raw(" "),
+            status_badge(last_run, &theme),
+        ];
+        let state = match workflow.state.as_str() {
+            "active" => None,
+            "disabled_manually" => Some("disabled".to_string()),
+            "disabled_inactivity" => Some("disabled: inactive".to_string()),
+            other => Some(other.replace('_', " ")),
+        };
+        if let Some(state) = state {
+            spans.push(Span::styled(
+                format!(" [{}]", state),
+                Style::default().fg(theme.warning),
+            ));
+        }
+        ListItem::new(Line::from(spans))
+    }));
+
+    let mut list_state = ListState::default();
+    list_state.select(Some(app.selected_workflow.map_or(0, |i| i + 1)));
+
+    let list =
+        List::new(items).highlight_style(Style::default().fg(theme.on_accent).bg(theme.accent));
+
+    f.render_stateful_widget(list, area.inner(Margin::new(1, 1)), &mut list_state);
+}
 
-    f.render_stateful_widget(list, area, &mut list_state);
+/// A status badge like the one GitHub serves for a workflow, from its
+/// latest run.
+fn status_badge(last_run: Option<&WorkflowRun>, theme: &Theme) -> Span<'static> {
+    let (label, color) = match last_run {
+        None => ("no status", theme.dim),
+        Some(run) if !run.is_completed() => ("running", theme.busy),
+        Some(run) if matches!(run.outcome(), "success" | "neutral" | "skipped") => {
+            ("passing", theme.success)
+        }
+        Some(_) => ("failing", theme.error),
+    };
+    Span::styled(
+        format!(" {} ", label),
+        Style::default().fg(theme.on_accent).bg(color),
+    )
 }
 
 fn render_actions_panel(f: &mut Frame, area: Rect, app: &AppState) {
+    let theme = theme();
+    let mut title = if app.actions_total > 0 {
+        format!("Action Runs ({}/{})", app.actions.len(), app.actions_total)
+    } else {
+        "Action Runs".to_string()
+    };
+    if let Some(workflow) = app.workflow_filter() {
+        title.push_str(&format!(" · {}", workflow.name));
+    }
+    if app.loading_actions {
+        title.push(' ');
+        title.push(app.spinner());
+    }
+    if app.pending_dispatch.is_some() {
+        title.push_str(&format!(" · {} waiting for dispatched run", app.spinner()));
+    }
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Action Runs")
+        .title(title)
         .border_style(if app.current_panel == Panel::Actions {
-            Color::Yellow
+            theme.accent
         } else {
-            Color::Gray
+            theme.muted
         });
 
     f.render_widget(block, area);
 
     if app.repos.is_empty() || app.selected_repo.is_none() || app.actions.is_empty() {
-        let no_actions =
-            Paragraph::new("No repository selected").style(Style::default().fg(Color::Gray));
+        let message = if app.selected_repo.is_none() {
+            "No repository selected"
+        } else if app.loading_actions {
+            "Loading workflow runs..."
+        } else {
+            "No workflow runs"
+        };
+        let no_actions = Paragraph::new(message).style(Style::default().fg(theme.muted));
         f.render_widget(no_actions, area.inner(Margin::new(1, 1)));
         return;
     }
@@ -173,21 +382,24 @@ fn render_actions_panel(f: &mut Frame, area: Rect, app: &AppState) {
         .map(|(i, action)| {
             let is_selected = Some(i) == app.selected_action;
             let base_style = if is_selected {
-                Style::default().fg(Color::Black).bg(Color::Yellow)
+                Style::default().fg(theme.on_accent).bg(theme.accent)
             } else {
                 Style::default()
             };
 
-            let conclusion_str = action.conclusion.as_deref().unwrap_or("unknown");
-            let name_str = &action.name;
-            let time_str = action.updated_at.format("%H:%M").to_string();
+            let conclusion_str = action.outcome();
+            let name_str = format!("#{} {}", action.run_number, action.name);
+            let branch_str = action.head_branch.as_deref().unwrap_or("-");
+            let time_str = action.updated_at.format("%m-%d %H:%M").to_string();
 
             let content = Line::from(vec![
                 Span::raw(conclusion_str.to_owned()),
                 Span::raw(" "),
-                Span::styled(name_str.clone(), base_style),
+                Span::styled(name_str, base_style),
                 Span::raw(" "),
-                Span::styled(time_str, Style::default().fg(Color::Gray)),
+                Span::styled(branch_str.to_owned(), Style::default().fg(theme.info)),
+                Span::raw(" "),
+                Span::styled(time_str, Style::default().fg(theme.muted)),
             ]);
 
             ListItem::new(content)
@@ -200,19 +412,20 @@ fn render_actions_panel(f: &mut Frame, area: Rect, app: &AppState) {
     }
 
     let list =
-        List::new(items).highlight_style(Style::default().fg(Color::Black).bg(Color::Yellow));
+        List::new(items).highlight_style(Style::default().fg(theme.on_accent).bg(theme.accent));
 
-    f.render_stateful_widget(list, area, &mut list_state);
+    f.render_stateful_widget(list, area.inner(Margin::new(1, 1)), &mut list_state);
 }
 
 fn render_details_panel(f: &mut Frame, area: Rect, app: &AppState) {
+    let theme = theme();
     let block = Block::default()
         .borders(Borders::ALL)
         .title("Details")
         .border_style(if app.current_panel == Panel::Details {
-            Color::Yellow
+            theme.accent
         } else {
-            Color::Gray
+            theme.muted
         });
 
     f.render_widget(block, area);
@@ -221,78 +434,450 @@ fn render_details_panel(f: &mut Frame, area: Rect, app: &AppState) {
 
     if app.selected_action.is_none() {
         let no_details =
-            Paragraph::new("No action selected").style(Style::default().fg(Color::Gray));
+            Paragraph::new("No action selected").style(Style::default().fg(theme.muted));
         f.render_widget(no_details, details_area);
     } else if let Some(selected_index) = app.selected_action {
         if selected_index < app.actions.len() {
             let action = &app.actions[selected_index];
 
-            let details = vec![
-                Line::from(vec![
-                    Span::styled("Name: ", Style::default().fg(Color::Cyan)),
-                    Span::styled(&action.name, Style::default()),
-                ]),
+            let field = |label: &'static str, value: String| {
                 Line::from(vec![
-                    Span::styled("Status: ", Style::default().fg(Color::Cyan)),
-                    Span::styled(&action.status, Style::default()),
-                ]),
-                Line::from(vec![
-                    Span::styled("Created: ", Style::default().fg(Color::Cyan)),
-                    Span::styled(
-                        action.created_at.format("%Y-%m-%d %H:%M").to_string(),
-                        Style::default(),
-                    ),
-                ]),
+                    Span::styled(label, Style::default().fg(theme.info)),
+                    Span::styled(value, Style::default()),
+                ])
+            };
+
+            let details = vec![
+                field("Name: ", action.name.clone()),
+                field("Run: ", format!("#{}", action.run_number)),
+                field("Status: ", action.status.clone()),
+                field(
+                    "Conclusion: ",
+                    action.conclusion.clone().unwrap_or_else(|| "-".to_string()),
+                ),
+                field("Event: ", action.event.clone()),
+                field(
+                    "Branch: ",
+                    action.head_branch.clone().unwrap_or_else(|| "-".to_string()),
+                ),
+                field("Commit: ", action.head_sha.chars().take(7).collect()),
+                field(
+                    "Actor: ",
+                    action.actor.clone().unwrap_or_else(|| "-".to_string()),
+                ),
+                field(
+                    "Created: ",
+                    action.created_at.format("%Y-%m-%d %H:%M").to_string(),
+                ),
+                field(
+                    "Started: ",
+                    action
+                        .run_started_at
+                        .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                ),
+                field(
+                    "Updated: ",
+                    action.updated_at.format("%Y-%m-%d %H:%M").to_string(),
+                ),
+                field("URL: ", action.html_url.clone()),
             ];
 
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(details.len() as u16), Constraint::Min(0)])
+                .split(details_area);
+
             let details_paragraph = Paragraph::new(details)
                 .wrap(Wrap { trim: true })
-                .style(Style::default().fg(Color::White));
+                .style(Style::default().fg(theme.text));
 
-            f.render_widget(details_paragraph, details_area);
+            f.render_widget(details_paragraph, chunks[0]);
+            render_jobs(f, chunks[1], app);
         } else {
             let no_details =
-                Paragraph::new("Invalid action selection").style(Style::default().fg(Color::Gray));
+                Paragraph::new("Invalid action selection").style(Style::default().fg(theme.muted));
             f.render_widget(no_details, details_area);
         }
     } else {
         let no_details =
-            Paragraph::new("Select a repository first").style(Style::default().fg(Color::Gray));
+            Paragraph::new("Select a repository first").style(Style::default().fg(theme.muted));
         f.render_widget(no_details, details_area);
     }
 }
 
+/// The selected run's jobs, or the steps of the selected job once opened.
+fn render_jobs(f: &mut Frame, area: Rect, app: &AppState) {
+    let theme = theme();
+    let open_job = app
+        .selected_step
+        .and(app.selected_job)
+        .and_then(|i| app.jobs.get(i));
+
+    let mut title = match open_job {
+        Some(job) => format!("Steps · {}", job.name),
+        None if app.jobs.is_empty() => "Jobs".to_string(),
+        None => format!("Jobs ({})", app.jobs.len()),
+    };
+    if app.loading_jobs {
+        title.push(' ');
+        title.push(app.spinner());
+    }
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .title(title)
+        .border_style(Style::default().fg(theme.muted));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if app.jobs.is_empty() {
+        let message = if app.loading_jobs { "Loading jobs..." } else { "No jobs" };
+        f.render_widget(
+            Paragraph::new(message).style(Style::default().fg(theme.muted)),
+            inner,
+        );
+        return;
+    }
+
+    let (items, selected): (Vec<ListItem>, _) = match open_job {
+        Some(job) => (
+            job.steps
+                .iter()
+                .map(|step| {
+                    ListItem::new(Line::from(vec![
+                        outcome_icon(step.outcome(), &theme),
+                        Span::raw(format!(" {}", step.name)),
+                        Span::styled(
+                            step.duration()
+                                .map(|d| format!("  {}", format_duration(d)))
+                                .unwrap_or_default(),
+                            Style::default().fg(theme.muted),
+                        ),
+                    ]))
+                })
+                .collect(),
+            app.selected_step,
+        ),
+        None => (
+            app.jobs
+                .iter()
+                .map(|job| {
+                    let runner = job.runner_name.as_deref().unwrap_or("no runner yet");
+                    ListItem::new(vec![
+                        Line::from(vec![
+                            outcome_icon(job.outcome(), &theme),
+                            Span::raw(format!(" {}", job.name)),
+                            Span::styled(
+                                job.duration()
+                                    .map(|d| format!("  {}", format_duration(d)))
+                                    .unwrap_or_default(),
+                                Style::default().fg(theme.muted),
+                            ),
+                        ]),
+                        Line::styled(
+                            format!("  {} · {}", runner, job.outcome()),
+                            Style::default().fg(theme.dim),
+                        ),
+                    ])
+                })
+                .collect(),
+            app.selected_job,
+        ),
+    };
+
+    let mut list_state = ListState::default();
+    list_state.select(selected);
+    let list =
+        List::new(items).highlight_style(Style::default().fg(theme.on_accent).bg(theme.accent));
+    f.render_stateful_widget(list, inner, &mut list_state);
+}
+
+/// Status icon for a job or step outcome.
+fn outcome_icon(outcome: &str, theme: &Theme) -> Span<'static> {
+    let (icon, color) = match outcome {
+        "success" => ("✓", theme.success),
+        "failure" | "timed_out" | "startup_failure" => ("✗", theme.error),
+        "cancelled" | "skipped" | "neutral" => ("⊘", theme.muted),
+        "action_required" => ("!", theme.warning),
+        "in_progress" => ("●", theme.busy),
+        // queued, waiting, pending, requested
+        _ => ("○", theme.muted),
+    };
+    Span::styled(icon, Style::default().fg(color))
+}
+
 fn render_footer(f: &mut Frame, area: Rect, app: &AppState) {
+    let theme = theme();
+    let keys = &config::get().keybindings;
+    let key = |action| keys.label(action);
     let help_text = match &app.auth_state {
-        AuthState::Authenticated { .. } => {
-            "[L]ogout [←→]Navigate Panels [↑↓]Navigate Items [Tab]Switch Panels [Q]uit"
-        }
-        _ => "[L]ogin [Q]uit",
+        AuthState::Authenticated { .. } => format!(
+            "[{}] Logout [{}] Accounts [{}{}] Navigate Panels [{}{}] Navigate Items [{}] Switch Panels [{}] Logs [{}] Workflow file [{}] Dispatch [{}] Quit",
+            key(Action::Login),
+            key(Action::Accounts),
+            key(Action::Left),
+            key(Action::Right),
+            key(Action::Up),
+            key(Action::Down),
+            key(Action::NextPanel),
+            key(Action::Logs),
+            key(Action::WorkflowFile),
+            key(Action::Dispatch),
+            key(Action::Quit),
+        ) + &run_action_help(app),
+        _ => format!(
+            "[{}] Login [{}] Accounts [{}] Quit",
+            key(Action::Login),
+            key(Action::Accounts),
+            key(Action::Quit),
+        ),
     };
 
-    let footer = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::Gray))
-        .block(Block::default().borders(Borders::ALL));
+    let mut block = Block::default().borders(Borders::ALL);
+    if let Some(rate_limit) = rate_limit_line(&theme) {
+        block = block.title_top(rate_limit.right_aligned());
+    }
+    let help_line = match (&app.log_view, &app.yaml_view) {
+        (Some(view), _) => log_view::status_line(view, &theme),
+        (None, Some(view)) => yaml_view::status_line(view, &theme),
+        (None, None) => Line::styled(help_text, Style::default().fg(theme.muted)),
+    };
+    let footer = Paragraph::new(help_line).block(block);
 
     f.render_widget(footer, area);
 }
 
-fn render_loading_overlay(f: &mut Frame) {
-    let area = f.area();
+/// Keys for acting on the selected workflow or run, in the panels that show one.
+fn run_action_help(app: &AppState) -> String {
+    let keys = &config::get().keybindings;
+    if app.current_panel == Panel::Workflows {
+        return match app.workflow_filter() {
+            Some(workflow) if workflow.is_active() => {
+                format!(" [{}] Disable", keys.label(Action::ToggleWorkflow))
+            }
+            Some(_) => format!(" [{}] Enable", keys.label(Action::ToggleWorkflow)),
+            None => String::new(),
+        };
+    }
+    if app.current_panel == Panel::Repositories || app.selected_run().is_none() {
+        return String::new();
+    }
+    format!(
+        " [{}] Re-run [{}] Re-run failed [{}] Cancel [{}] Force cancel",
+        keys.label(Action::Rerun),
+        keys.label(Action::RerunFailed),
+        keys.label(Action::Cancel),
+        keys.label(Action::ForceCancel),
+    )
+}
+
+/// The API budget left, and a countdown to its reset while requests or
+/// polling are held back.
+fn rate_limit_line(theme: &Theme) -> Option<Line<'static>> {
+    if let Some(until) = rate_limit::blocked_until() {
+        return Some(Line::styled(
+            format!("Rate limited · resets in {}", format_wait(until)),
+            Style::default().fg(theme.error),
+        ));
+    }
+    let budget = rate_limit::latest()?;
+    let remaining = format!("API {}/{}", budget.remaining, budget.limit);
+    Some(match rate_limit::polling_paused_until() {
+        Some(until) => Line::styled(
+            format!(
+                "{} · polling paused for {}",
+                remaining,
+                format_wait(until)
+            ),
+            Style::default().fg(theme.warning),
+        ),
+        None => Line::styled(remaining, Style::default().fg(theme.muted)),
+    })
+}
 
-    let loading_text = vec![Line::from("Loading..."), Line::from("Please wait")];
+fn render_popup(f: &mut Frame, popup: &Popup) {
+    let theme = theme();
+    let key = |k: &'static str| Span::styled(k, Style::default().fg(theme.accent));
+
+    let (title, lines) = match popup {
+        Popup::LoginMethod => (
+            "Login",
+            vec![
+                Line::from("Choose how to sign in to GitHub:"),
+                Line::from(""),
+                Line::from(vec![key("[B]"), Span::raw(" Browser (OAuth redirect)")]),
+                Line::from(vec![key("[D]"), Span::raw(" Device code (headless / SSH)")]),
+                Line::from(vec![key("[T]"), Span::raw(" Personal access token")]),
+                Line::from(""),
+                Line::from(vec![key("[Esc]"), Span::raw(" Cancel")]),
+            ],
+        ),
+        Popup::BrowserLogin {
+            authorize_url,
+            browser_error,
+        } => (
+            "Browser Login",
+            vec![
+                Line::from(Span::styled(
+                    format!("Could not open a browser: {}", browser_error),
+                    Style::default().fg(theme.warning),
+                )),
+                Line::from(""),
+                Line::from("Open this URL on this machine to sign in:"),
+                Line::from(Span::styled(
+                    authorize_url.as_str(),
+                    Style::default().fg(theme.info),
+                )),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "Waiting for GitHub to redirect back...",
+                    Style::default().fg(theme.muted),
+                )),
+                Line::from(""),
+                Line::from(vec![key("[Esc]"), Span::raw(" Cancel")]),
+            ],
+        ),
+        Popup::DeviceCode {
+            authorization,
+            status,
+        } => {
+            let remaining = authorization
+                .expires_at
+                .saturating_duration_since(Instant::now())
+                .as_secs();
+            (
+                "Device Login",
+                vec![
+                    Line::from("On any device, open:"),
+                    Line::from(Span::styled(
+                        authorization.verification_uri.as_str(),
+                        Style::default().fg(theme.info),
+                    )),
+                    Line::from(""),
+                    Line::from("and enter the code:"),
+                    Line::from(Span::styled(
+                        authorization.user_code.as_str(),
+                        Style::default()
+                            .fg(theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    )),
+                    Line::from(""),
+                    Line::from(format!(
+                        "Code expires in {}:{:02}",
+                        remaining / 60,
+                        remaining % 60
+                    )),
+                    Line::from(Span::styled(
+                        status.as_str(),
+                        Style::default().fg(theme.muted),
+                    )),
+                    Line::from(""),
+                    Line::from(vec![key("[Esc]"), Span::raw(" Cancel")]),
+                ],
+            )
+        }
+        Popup::TokenInput { input, error } => {
+            let mut lines = vec![
+                Line::from("Paste a classic or fine-grained personal access token:"),
+                Line::from(""),
+                Line::from(Span::styled(
+                    format!("{}▏", "•".repeat(input.chars().count())),
+                    Style::default().fg(theme.info),
+                )),
+                Line::from(""),
+            ];
+            if let Some(error) = error {
+                lines.push(Line::from(Span::styled(
+                    error.as_str(),
+                    Style::default().fg(theme.error),
+                )));
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(vec![
+                key("[Enter]"),
+                Span::raw(" Validate & save  "),
+                key("[Esc]"),
+                Span::raw(" Cancel"),
+            ]));
+            ("Token Login", lines)
+        }
+        Popup::AccountPicker { accounts, selected } => {
+            let mut lines = vec![Line::from("Stored accounts:"), Line::from("")];
+            if accounts.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    "No accounts yet",
+                    Style::default().fg(theme.muted),
+                )));
+            }
+            for (i, (account, active)) in accounts.iter().enumerate() {
+                let style = if i == *selected {
+                    Style::default().fg(theme.on_accent).bg(theme.accent)
+                } else {
+                    Style::default()
+                };
+                let marker = if *active { "● " } else { "  " };
+                lines.push(Line::from(Span::styled(
+                    format!("{}{} @ {}", marker, account.login, account.host),
+                    style,
+                )));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                key("[Enter]"),
+                Span::raw(" Switch  "),
+                key("[N]"),
+                Span::raw(" Add account  "),
+                key("[Esc]"),
+                Span::raw(" Close"),
+            ]));
+            ("Accounts", lines)
+        }
+        Popup::ConfirmRunAction {
+            action,
+            run_label,
+            debug_logging,
+            ..
+        } => {
+            let mut lines = vec![Line::from(action.describe(run_label)), Line::from("")];
+            if action.is_rerun() {
+                let (state, style) = if *debug_logging {
+                    ("on", Style::default().fg(theme.warning))
+                } else {
+                    ("off", Style::default().fg(theme.muted))
+                };
+                lines.push(Line::from(vec![
+                    key("[D]"),
+                    Span::raw(" Debug logging: "),
+                    Span::styled(state, style),
+                ]));
+                lines.push(Line::from(""));
+            }
+            lines.push(Line::from(vec![
+                key("[Enter]"),
+                Span::raw(" Confirm  "),
+                key("[Esc]"),
+                Span::raw(" Cancel"),
+            ]));
+            let title = if action.is_rerun() { "Re-run" } else { "Cancel Run" };
+            (title, lines)
+        }
+        // Drawn by `dispatch::render_dispatch`
+        Popup::Dispatch(_) => return,
+    };
 
-    let paragraph = Paragraph::new(loading_text)
-        .style(Style::default().fg(Color::White))
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Blue)),
+                .title(title)
+                .border_style(Style::default().fg(theme.accent)),
         );
 
-    let popup_area = centered_rect(60, 20, area);
+    let popup_area = centered_rect(60, 50, f.area());
 
-    f.render_widget(Clear, area);
+    f.render_widget(Clear, popup_area);
     f.render_widget(paragraph, popup_area);
 }
 
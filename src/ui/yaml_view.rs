@@ -0,0 +1,232 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::app::yaml_view::{WorkflowSource, YamlState, YamlView};
+use crate::config::Theme;
+use super::theme;
+
+/// Draw a workflow file over the main panels, with line numbers and YAML
+/// syntax highlighting.
+pub fn render_yaml_view(f: &mut Frame, area: Rect, view: &YamlView, spinner: char) {
+    let theme = theme();
+    // Commits are shown abbreviated, like GitHub does
+    let git_ref = if view.git_ref.len() == 40 && view.git_ref.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        &view.git_ref[..7]
+    } else {
+        view.git_ref.as_str()
+    };
+    let position = match view.line_count() {
+        0 => String::new(),
+        count => format!("line {}/{}", view.cursor + 1, count),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Workflow · {} @ {}", view.path, git_ref))
+        .title_top(Line::styled(position, Style::default().fg(theme.muted)).right_aligned())
+        .border_style(Style::default().fg(theme.accent));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    match &view.state {
+        YamlState::Loading => {
+            let loading = Paragraph::new(format!("{} Fetching workflow file...", spinner))
+                .style(Style::default().fg(theme.busy));
+            f.render_widget(loading, inner);
+        }
+        YamlState::Failed(message) => {
+            let failed = Paragraph::new(message.as_str()).style(Style::default().fg(theme.error));
+            f.render_widget(failed, inner);
+        }
+        YamlState::Loaded(source) => render_lines(f, inner, view, source, &theme),
+    }
+}
+
+fn render_lines(f: &mut Frame, area: Rect, view: &YamlView, source: &WorkflowSource, theme: &Theme) {
+    let height = area.height as usize;
+    view.height.set(height);
+
+    // Scroll only as far as needed to keep the cursor on screen
+    let mut scroll = view.scroll.get();
+    if view.cursor < scroll {
+        scroll = view.cursor;
+    } else if height > 0 && view.cursor >= scroll + height {
+        scroll = view.cursor + 1 - height;
+    }
+    scroll = scroll.min(source.lines.len().saturating_sub(height));
+    view.scroll.set(scroll);
+
+    let number_width = source.lines.len().to_string().len();
+    let lines: Vec<Line> = source
+        .lines
+        .iter()
+        .zip(&source.block_lines)
+        .enumerate()
+        .skip(scroll)
+        .take(height)
+        .map(|(index, (line, &in_block))| {
+            let mut spans = vec![Span::styled(
+                format!("{:>width$} ", index + 1, width = number_width),
+                Style::default().fg(theme.dim),
+            )];
+            if in_block {
+                spans.extend(expression_spans(line, Style::default(), theme));
+            } else {
+                spans.extend(yaml_spans(line, theme));
+            }
+
+            let mut rendered = Line::from(spans);
+            if index == view.cursor {
+                rendered = rendered.style(Style::default().bg(theme.dim));
+            }
+            rendered
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+/// A line of YAML styled by what its parts are: keys, values, comments and
+/// `${{ }}` expressions.
+fn yaml_spans(line: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let punctuation = Style::default().fg(theme.dim);
+    let code = line.trim_start();
+    let mut spans = vec![Span::raw(line[..line.len() - code.len()].to_string())];
+    if code == "---" || code == "..." {
+        spans.push(Span::styled(code.to_string(), punctuation));
+        return spans;
+    }
+
+    let (mut code, comment) = split_comment(code);
+    loop {
+        if let Some(rest) = code.strip_prefix("- ") {
+            spans.push(Span::styled("- ", punctuation));
+            code = rest;
+        } else if code == "-" {
+            spans.push(Span::styled("-", punctuation));
+            code = "";
+        } else {
+            break;
+        }
+    }
+    if let Some((key, value)) = split_key(code) {
+        spans.push(Span::styled(
+            key.to_string(),
+            Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(":", punctuation));
+        code = value;
+    }
+    spans.extend(value_spans(code, theme));
+    if !comment.is_empty() {
+        spans.push(Span::styled(
+            comment.to_string(),
+            Style::default().fg(theme.muted).add_modifier(Modifier::ITALIC),
+        ));
+    }
+    spans
+}
+
+/// Split off a trailing `# comment`, which starts at a `#` after whitespace
+/// outside quotes.
+fn split_comment(code: &str) -> (&str, &str) {
+    let mut quote = None;
+    let mut previous = ' ';
+    for (i, c) in code.char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '#' && previous.is_whitespace() => return code.split_at(i),
+            None if matches!(c, '"' | '\'') && (previous.is_whitespace() || i == 0) => {
+                quote = Some(c)
+            }
+            None => {}
+        }
+        previous = c;
+    }
+    (code, "")
+}
+
+/// Split `key: value` into the key and what follows the colon.
+fn split_key(code: &str) -> Option<(&str, &str)> {
+    if let Some(quote) = code.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+        let end = code[1..].find(quote)? + 2;
+        let rest = code[end..].strip_prefix(':')?;
+        return (rest.is_empty() || rest.starts_with(' ')).then(|| (&code[..end], rest));
+    }
+    if code.starts_with(['{', '[', '|', '>', '&', '*', '!']) {
+        return None;
+    }
+    let colon = code
+        .find(": ")
+        .or_else(|| code.ends_with(':').then(|| code.len() - 1))?;
+    let key = &code[..colon];
+    (!key.is_empty() && !key.contains("${{")).then(|| (key, &code[colon + 1..]))
+}
+
+fn value_spans(value: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let text = value.trim_start();
+    let mut spans = vec![Span::raw(value[..value.len() - text.len()].to_string())];
+    let literal = Style::default().fg(theme.info);
+    let style = if text.starts_with(['"', '\'']) {
+        Style::default().fg(theme.success)
+    } else if text.starts_with(['|', '>']) {
+        Style::default().fg(theme.dim)
+    } else if text.starts_with(['&', '*', '!']) {
+        // An anchor, alias or tag, then maybe a value
+        let (marker, rest) = text.split_once(' ').unwrap_or((text, ""));
+        spans.push(Span::styled(
+            marker.to_string(),
+            Style::default().fg(theme.warning),
+        ));
+        if !rest.is_empty() {
+            spans.push(Span::raw(" "));
+            spans.extend(value_spans(rest, theme));
+        }
+        return spans;
+    } else if matches!(text, "true" | "false" | "null" | "~") || text.parse::<f64>().is_ok() {
+        literal
+    } else {
+        Style::default()
+    };
+    spans.extend(expression_spans(text, style, theme));
+    spans
+}
+
+/// `text` in `style`, with its `${{ }}` expressions standing out.
+fn expression_spans(text: &str, style: Style, theme: &Theme) -> Vec<Span<'static>> {
+    let expression = Style::default().fg(theme.warning);
+    let mut spans = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${{") {
+        let end = rest[start..]
+            .find("}}")
+            .map_or(rest.len(), |end| start + end + 2);
+        if start > 0 {
+            spans.push(Span::styled(rest[..start].to_string(), style));
+        }
+        spans.push(Span::styled(rest[start..end].to_string(), expression));
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        spans.push(Span::styled(rest.to_string(), style));
+    }
+    spans
+}
+
+/// The footer line of the YAML viewer: feedback from the last command, or
+/// the keys.
+pub fn status_line(view: &YamlView, theme: &Theme) -> Line<'static> {
+    if let Some(message) = &view.message {
+        return Line::styled(message.clone(), Style::default().fg(theme.warning));
+    }
+    Line::styled(
+        "[↑↓] Scroll [PgUp/PgDn] Page [g/G] Top/Bottom [[/]] Previous/Next job [Esc] Close",
+        Style::default().fg(theme.muted),
+    )
+}
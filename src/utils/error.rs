@@ -14,8 +14,27 @@ pub enum AppError {
     #[error("Keyring error: {0}")]
     KeyringError(#[from] keyring::Error),
 
+    #[error("Credential store error: {0}")]
+    CredentialStoreError(String),
+
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+
     #[error("GitHub API error: {0}")]
-    GitHubError(#[from] octocrab::Error),
+    GitHubError(Box<octocrab::Error>),
+
+    /// GitHub answered with a server error, often with an HTML body octocrab
+    /// can't parse.
+    #[error("GitHub is unavailable (HTTP {0})")]
+    GitHubUnavailable(u16),
+
+    /// GitHub rejected the request for exceeding a rate limit; requests may
+    /// be made again after the given time.
+    #[error(
+        "GitHub API rate limit exceeded; try again in {}",
+        crate::utils::format::format_wait(*.0)
+    )]
+    RateLimited(chrono::DateTime<chrono::Utc>),
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -23,6 +42,9 @@ pub enum AppError {
     #[error("Server error: {0}")]
     ServerError(String),
 
+    #[error("Invalid workflow file: {0}")]
+    InvalidWorkflow(String),
+
     #[error("JSON error: {0}")]
     SerializationError(#[from] serde_json::Error),
 
@@ -39,7 +61,39 @@ pub enum AppError {
     UserCancelled,
 }
 
+// Boxed because octocrab's error carries a backtrace and would otherwise
+// bloat every `Result<_, AppError>` in the crate.
+impl From<octocrab::Error> for AppError {
+    fn from(error: octocrab::Error) -> Self {
+        AppError::GitHubError(Box::new(error))
+    }
+}
+
 impl AppError {
+    /// Whether the failure is likely transient, so the request is worth
+    /// sending again: timeouts, connection failures, server errors and
+    /// secondary rate limits that clear within a minute.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AppError::NetworkError(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status().is_some_and(|status| status.is_server_error())
+            }
+            AppError::GitHubError(e) => match e.as_ref() {
+                octocrab::Error::GitHub { source, .. } => source.status_code.is_server_error(),
+                octocrab::Error::Hyper { .. } | octocrab::Error::Service { .. } => true,
+                _ => false,
+            },
+            AppError::GitHubUnavailable(_) => true,
+            AppError::RateLimited(until) => {
+                (*until - chrono::Utc::now()).to_std().unwrap_or_default()
+                    <= crate::utils::retry::MAX_RETRY_WAIT
+            }
+            _ => false,
+        }
+    }
+
     pub fn user_message(&self) -> String {
         match self {
             AppError::AuthFailed(msg) => format!("❌ Authentication failed: {}", msg),
@@ -58,9 +112,21 @@ impl AppError {
             AppError::KeyringError(_) => {
                 "🔐 Failed to access secure storage. Please check your system keyring.".to_string()
             }
+            AppError::CredentialStoreError(msg) => {
+                format!("🔐 Credential store error: {}", msg)
+            }
+            AppError::ConfigError(msg) => format!("⚙️ Invalid configuration: {}", msg),
             AppError::GitHubError(e) => {
                 format!("🐙 GitHub API error: {}", e)
             }
+            AppError::GitHubUnavailable(status) => format!(
+                "🐙 GitHub is having trouble (HTTP {}). Please try again shortly.",
+                status
+            ),
+            AppError::RateLimited(until) => format!(
+                "⏳ GitHub rate limit reached - resets at {}",
+                until.with_timezone(&chrono::Local).format("%H:%M")
+            ),
             AppError::NoAvailablePorts => {
                 "🚫 No available ports for local server. Please try again.".to_string()
             }
@@ -69,6 +135,7 @@ impl AppError {
                 format!("🚫 Server error: {}", msg)
             }
             AppError::UserCancelled => "❌ Authentication was cancelled.".to_string(),
+            AppError::InvalidWorkflow(msg) => format!("📄 Invalid workflow file: {}", msg),
             _ => format!("❌ An error occurred: {}", self),
         }
     }
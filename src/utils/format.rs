@@ -0,0 +1,29 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// A duration as `45s`, `3m 05s` or `1h 02m`.
+pub fn format_duration(duration: TimeDelta) -> String {
+    let secs = duration.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Time left until `until`, formatted like [`format_duration`].
+pub fn format_wait(until: DateTime<Utc>) -> String {
+    format_duration(until - Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(TimeDelta::seconds(-5)), "0s");
+        assert_eq!(format_duration(TimeDelta::seconds(45)), "45s");
+        assert_eq!(format_duration(TimeDelta::seconds(185)), "3m 05s");
+        assert_eq!(format_duration(TimeDelta::seconds(3720)), "1h 02m");
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{SecondsFormat, Utc};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::config::{LOG_FILE, SERVICE_NAME};
+
+/// Appends log records to a file, since the TUI owns the terminal and
+/// anything written to stderr would corrupt it.
+struct FileLogger {
+    file: Mutex<File>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut file = self.file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = writeln!(
+            file,
+            "{} {:<5} {}: {}",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {
+        let mut file = self.file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = file.flush();
+    }
+}
+
+/// `$XDG_STATE_HOME/oxa/oxa.log`, or the cache directory on platforms
+/// without a state directory.
+pub fn log_path() -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join(SERVICE_NAME).join(LOG_FILE))
+}
+
+/// Send log records to the log file. Logging is best effort: when the file
+/// can't be opened, records are dropped.
+pub fn init() {
+    let Some(path) = log_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let Ok(file) = OpenOptions::new().create(true).append(true).open(&path) else {
+        return;
+    };
+
+    let logger = FileLogger {
+        file: Mutex::new(file),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
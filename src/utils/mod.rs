@@ -1 +1,4 @@
 pub mod error;
+pub mod format;
+pub mod logging;
+pub mod retry;
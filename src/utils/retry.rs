@@ -1,31 +1,44 @@
+use chrono::Utc;
+use rand::Rng;
+use std::future::Future;
 use std::time::Duration;
 use tokio::time::sleep;
-use utils::error::AppError;
 
-/// Retry with exponential backoff
-pub async fn retry_with_backoff<F, T, E>(
+use super::error::AppError;
+
+/// Retry with exponential backoff and jitter. Only errors that
+/// [`AppError::is_retryable`] accepts are retried; when GitHub said how long
+/// to wait, that is waited instead.
+pub async fn retry_with_backoff<F, Fut, T>(
     mut operation: F,
     max_attempts: u32,
     initial_delay: Duration,
-) -> Result<T, E>
+) -> Result<T, AppError>
 where
-    F: FnMut() -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, E>> + Send>>,
-    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
 {
     let mut delay = initial_delay;
-    
+
     for attempt in 1..=max_attempts {
         match operation().await {
             Ok(result) => return Ok(result),
-            Err(e) if attempt < max_attempts => {
-                eprintln!("Attempt {} failed: {}. Retrying in {}s...", attempt, e, delay.as_secs());
-                sleep(delay).await;
+            Err(e) if attempt < max_attempts && is_retryable_error(&e) => {
+                let wait = retry_after(&e).unwrap_or_else(|| jitter(delay));
+                log::warn!(
+                    "Attempt {} of {} failed: {}. Retrying in {:.1}s",
+                    attempt,
+                    max_attempts,
+                    e,
+                    wait.as_secs_f64()
+                );
+                sleep(wait).await;
                 delay *= 2; // Exponential backoff
             }
             Err(e) => return Err(e),
         }
     }
-    
+
     unreachable!()
 }
 
@@ -34,8 +47,122 @@ pub fn is_retryable_error(error: &AppError) -> bool {
     error.is_retryable()
 }
 
+/// How long GitHub asked us to wait before the next attempt, if it did.
+fn retry_after(error: &AppError) -> Option<Duration> {
+    match error {
+        AppError::RateLimited(until) => Some((*until - Utc::now()).to_std().unwrap_or_default()),
+        _ => None,
+    }
+}
+
+/// Spread retries over 50-150% of `delay` so clients that failed together
+/// don't retry together.
+fn jitter(delay: Duration) -> Duration {
+    delay.mul_f64(rand::thread_rng().gen_range(0.5..1.5))
+}
+
 /// Maximum retry attempts for network operations
 pub const MAX_RETRIES: u32 = 3;
 
 /// Initial delay for retry logic (in seconds)
-pub const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
\ No newline at end of file
+pub const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest rate limit wait worth retrying through; secondary limits usually
+/// ask for a minute at most, while a spent budget can take an hour to reset.
+pub const MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+    use http_body_util::{BodyExt, Full};
+
+    const NO_DELAY: Duration = Duration::from_millis(1);
+
+    /// The error octocrab reports for a response with `status`.
+    async fn github_error(status: u16) -> AppError {
+        let body = Full::from(r#"{"message":"failed"}"#)
+            .map_err(|never| match never {})
+            .boxed();
+        let response = http::Response::builder().status(status).body(body).unwrap();
+        octocrab::map_github_error(response).await.unwrap_err().into()
+    }
+
+    /// Run `retry_with_backoff` over `errors`, one per attempt until they
+    /// run out, and return the result with the number of attempts made.
+    async fn attempts(mut errors: Vec<AppError>) -> (Result<(), AppError>, u32) {
+        errors.reverse();
+        let mut attempts = 0;
+        let result = retry_with_backoff(
+            || {
+                attempts += 1;
+                let result = errors.pop().map_or(Ok(()), Err);
+                async move { result }
+            },
+            MAX_RETRIES,
+            NO_DELAY,
+        )
+        .await;
+        (result, attempts)
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors() {
+        let (result, attempts) =
+            attempts(vec![github_error(502).await, AppError::GitHubUnavailable(503)]).await;
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let errors = (0..MAX_RETRIES).map(|_| AppError::GitHubUnavailable(500)).collect();
+        let (result, attempts) = attempts(errors).await;
+        assert!(matches!(result, Err(AppError::GitHubUnavailable(500))));
+        assert_eq!(attempts, MAX_RETRIES);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_client_errors() {
+        for status in [401, 404, 422] {
+            let (result, attempts) = attempts(vec![github_error(status).await]).await;
+            assert!(matches!(result, Err(AppError::GitHubError(_))), "{}", status);
+            assert_eq!(attempts, 1, "{}", status);
+        }
+        let (result, attempts) = attempts(vec![AppError::AuthFailed("bad".to_string())]).await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn retries_short_rate_limits() {
+        // Already over, so the retry doesn't have to wait
+        let until = Utc::now() - TimeDelta::seconds(1);
+        let (result, attempts) = attempts(vec![AppError::RateLimited(until)]).await;
+        assert!(result.is_ok());
+        assert_eq!(attempts, 2);
+
+        let until = Utc::now() + TimeDelta::seconds(30);
+        assert!(is_retryable_error(&AppError::RateLimited(until)));
+        let wait = retry_after(&AppError::RateLimited(until)).unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn does_not_wait_out_long_rate_limits() {
+        let wait = TimeDelta::from_std(MAX_RETRY_WAIT).unwrap() + TimeDelta::minutes(1);
+        let until = Utc::now() + wait;
+        let (result, attempts) = attempts(vec![AppError::RateLimited(until)]).await;
+        assert!(matches!(result, Err(AppError::RateLimited(_))));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn jitter_stays_within_half_to_one_and_a_half() {
+        let delay = Duration::from_secs(4);
+        for _ in 0..1000 {
+            let wait = jitter(delay);
+            assert!(wait >= delay / 2 && wait < delay * 3 / 2, "{:?}", wait);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use crate::utils::error::AppError;
+use super::model::WorkflowFile;
+use super::yaml::Node;
+
+/// The type of a `workflow_dispatch` input, which decides how it is edited.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputKind {
+    String,
+    Boolean,
+    Number,
+    Choice(Vec<String>),
+    /// One of the repository's deployment environments, filled in once they
+    /// are known.
+    Environment(Vec<String>),
+}
+
+/// An input declared under `on.workflow_dispatch.inputs`, with the value the
+/// user has entered so far.
+#[derive(Debug, Clone)]
+pub struct DispatchInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: InputKind,
+    pub required: bool,
+    pub value: String,
+    /// Why the value was refused on the last attempt to dispatch.
+    pub error: Option<String>,
+}
+
+impl DispatchInput {
+    /// Step a boolean, choice or environment value to the next (or
+    /// previous) allowed value.
+    pub fn cycle(&mut self, forward: bool) {
+        let options = match &self.kind {
+            InputKind::Boolean => vec!["true".to_string(), "false".to_string()],
+            InputKind::Choice(options) | InputKind::Environment(options) => options.clone(),
+            InputKind::String | InputKind::Number => return,
+        };
+        if options.is_empty() {
+            return;
+        }
+        let next = match options.iter().position(|option| *option == self.value) {
+            Some(i) if forward => (i + 1) % options.len(),
+            Some(i) => (i + options.len() - 1) % options.len(),
+            None => 0,
+        };
+        self.value = options[next].clone();
+        self.error = None;
+    }
+
+    /// Whether the value is typed in rather than picked.
+    pub fn is_text(&self) -> bool {
+        match &self.kind {
+            InputKind::String | InputKind::Number => true,
+            // Without known environments any name may be entered
+            InputKind::Environment(options) => options.is_empty(),
+            InputKind::Boolean | InputKind::Choice(_) => false,
+        }
+    }
+
+    /// Check the value against the input's type, recording why it is
+    /// refused. Returns the value as sent to GitHub.
+    pub fn validate(&mut self) -> Option<serde_json::Value> {
+        let value = self.value.trim();
+        self.error = if value.is_empty() {
+            self.required.then(|| "required".to_string())
+        } else {
+            match &self.kind {
+                InputKind::Number if value.parse::<f64>().is_err() => {
+                    Some("must be a number".to_string())
+                }
+                InputKind::Choice(options) if !options.iter().any(|option| option == value) => {
+                    Some(format!("must be one of {}", options.join(", ")))
+                }
+                _ => None,
+            }
+        };
+        if self.error.is_some() {
+            return None;
+        }
+        Some(serde_json::Value::String(value.to_string()))
+    }
+}
+
+/// The inputs of the `workflow_dispatch` trigger of the workflow file
+/// `source`, in the order they are declared. `None` when the workflow can't
+/// be dispatched.
+pub fn dispatch_inputs(source: &str) -> Result<Option<Vec<DispatchInput>>, AppError> {
+    let workflow = WorkflowFile::parse(source)?;
+    let Some((_, settings)) = workflow
+        .triggers()
+        .into_iter()
+        .find(|(event, _)| event.as_str() == Some("workflow_dispatch"))
+    else {
+        return Ok(None);
+    };
+
+    let Some(inputs) = settings
+        .and_then(|settings| settings.get("inputs"))
+        .and_then(Node::as_mapping)
+    else {
+        return Ok(Some(Vec::new()));
+    };
+    let inputs = inputs
+        .iter()
+        .filter_map(|(name, input)| Some(parse_input(name.as_str()?, input)))
+        .collect();
+    Ok(Some(inputs))
+}
+
+fn parse_input(name: &str, input: &Node) -> DispatchInput {
+    let kind = match input.get("type").and_then(Node::as_str) {
+        Some("boolean") => InputKind::Boolean,
+        Some("number") => InputKind::Number,
+        Some("choice") => InputKind::Choice(
+            input
+                .get("options")
+                .and_then(Node::as_sequence)
+                .map(|options| options.iter().filter_map(scalar).collect())
+                .unwrap_or_default(),
+        ),
+        Some("environment") => InputKind::Environment(Vec::new()),
+        _ => InputKind::String,
+    };
+    // GitHub preselects the first choice and unchecks booleans
+    let value = input.get("default").and_then(scalar).unwrap_or_else(|| match &kind {
+        InputKind::Boolean => "false".to_string(),
+        InputKind::Choice(options) => options.first().cloned().unwrap_or_default(),
+        _ => String::new(),
+    });
+
+    DispatchInput {
+        name: name.to_string(),
+        description: input.get("description").and_then(scalar),
+        kind,
+        required: input
+            .get("required")
+            .and_then(Node::as_bool)
+            .unwrap_or(false),
+        value,
+        error: None,
+    }
+}
+
+/// A scalar value as the text it would be dispatched as.
+fn scalar(value: &Node) -> Option<String> {
+    value
+        .as_str()
+        .filter(|_| !value.is_null())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = "\
+on:
+  push:
+  workflow_dispatch:
+    inputs:
+      level:
+        description: Log level
+        type: choice
+        options: [info, warning, debug]
+      pinned:
+        type: choice
+        options: [a, b]
+        default: b
+      dry_run:
+        type: boolean
+      verbose:
+        type: boolean
+        default: true
+      retries:
+        type: number
+        default: 3
+      target:
+        type: environment
+        required: true
+      note:
+        required: true
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo hi
+";
+
+    fn inputs() -> Vec<DispatchInput> {
+        dispatch_inputs(WORKFLOW).unwrap().unwrap()
+    }
+
+    fn input(name: &str) -> DispatchInput {
+        inputs().into_iter().find(|input| input.name == name).unwrap()
+    }
+
+    #[test]
+    fn keeps_declaration_order() {
+        let names: Vec<String> = inputs().into_iter().map(|input| input.name).collect();
+        assert_eq!(
+            names,
+            ["level", "pinned", "dry_run", "verbose", "retries", "target", "note"],
+        );
+    }
+
+    #[test]
+    fn choice_defaults_to_first_option() {
+        let level = input("level");
+        assert_eq!(
+            level.kind,
+            InputKind::Choice(vec!["info".into(), "warning".into(), "debug".into()]),
+        );
+        assert_eq!(level.value, "info");
+        assert_eq!(level.description.as_deref(), Some("Log level"));
+        assert_eq!(input("pinned").value, "b");
+    }
+
+    #[test]
+    fn boolean_defaults_to_false() {
+        let dry_run = input("dry_run");
+        assert_eq!(dry_run.kind, InputKind::Boolean);
+        assert_eq!(dry_run.value, "false");
+        assert_eq!(input("verbose").value, "true");
+    }
+
+    #[test]
+    fn validates_numbers() {
+        let mut retries = input("retries");
+        assert_eq!(retries.value, "3");
+        assert_eq!(retries.validate(), Some(serde_json::json!("3")));
+
+        retries.value = " 2.5 ".to_string();
+        assert_eq!(retries.validate(), Some(serde_json::json!("2.5")));
+
+        retries.value = "three".to_string();
+        assert_eq!(retries.validate(), None);
+        assert_eq!(retries.error.as_deref(), Some("must be a number"));
+
+        // Optional inputs may be left empty
+        retries.value = String::new();
+        assert_eq!(retries.validate(), Some(serde_json::json!("")));
+        assert_eq!(retries.error, None);
+    }
+
+    #[test]
+    fn refuses_missing_required_input() {
+        let mut note = input("note");
+        assert_eq!(note.kind, InputKind::String);
+        assert!(note.required);
+        note.value = "  ".to_string();
+        assert_eq!(note.validate(), None);
+        assert_eq!(note.error.as_deref(), Some("required"));
+
+        note.value = "release".to_string();
+        assert_eq!(note.validate(), Some(serde_json::json!("release")));
+        assert_eq!(note.error, None);
+    }
+
+    #[test]
+    fn refuses_unknown_choice() {
+        let mut level = input("level");
+        level.value = "trace".to_string();
+        assert_eq!(level.validate(), None);
+        assert_eq!(level.error.as_deref(), Some("must be one of info, warning, debug"));
+    }
+
+    #[test]
+    fn cycles_through_options() {
+        let mut level = input("level");
+        level.error = Some("stale".to_string());
+        level.cycle(true);
+        assert_eq!(level.value, "warning");
+        assert_eq!(level.error, None);
+        level.cycle(false);
+        level.cycle(false);
+        assert_eq!(level.value, "debug");
+
+        let mut dry_run = input("dry_run");
+        dry_run.cycle(true);
+        assert_eq!(dry_run.value, "true");
+        dry_run.cycle(true);
+        assert_eq!(dry_run.value, "false");
+
+        // Environments can only be picked once they are known
+        let mut target = input("target");
+        assert!(target.is_text());
+        target.cycle(true);
+        assert_eq!(target.value, "");
+        target.kind = InputKind::Environment(vec!["staging".into(), "production".into()]);
+        target.cycle(false);
+        assert_eq!(target.value, "staging");
+    }
+
+    #[test]
+    fn dispatch_without_inputs() {
+        let source = "on: [push, workflow_dispatch]\njobs: {}\n";
+        assert_eq!(dispatch_inputs(source).unwrap().map(|inputs| inputs.len()), Some(0));
+        let source = "on:\n  workflow_dispatch:\njobs: {}\n";
+        assert_eq!(dispatch_inputs(source).unwrap().map(|inputs| inputs.len()), Some(0));
+    }
+
+    #[test]
+    fn no_inputs_without_workflow_dispatch() {
+        let source = "on:\n  push:\n    branches: [main]\njobs: {}\n";
+        assert!(dispatch_inputs(source).unwrap().is_none());
+        assert!(dispatch_inputs("on: push\njobs: {}\n").unwrap().is_none());
+    }
+}
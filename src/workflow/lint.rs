@@ -0,0 +1,884 @@
+use std::collections::{HashMap, HashSet};
+
+use super::model::{Job, WorkflowFile};
+use super::yaml::{Mark, Node, Value};
+
+/// A problem found in a workflow file, and where it is written.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Diagnostic {
+    pub mark: Mark,
+    pub message: String,
+}
+
+const WORKFLOW_KEYS: &[&str] = &[
+    "name",
+    "run-name",
+    "on",
+    "permissions",
+    "env",
+    "defaults",
+    "concurrency",
+    "jobs",
+];
+const JOB_KEYS: &[&str] = &[
+    "name",
+    "permissions",
+    "needs",
+    "if",
+    "runs-on",
+    "environment",
+    "concurrency",
+    "outputs",
+    "env",
+    "defaults",
+    "steps",
+    "timeout-minutes",
+    "strategy",
+    "continue-on-error",
+    "container",
+    "services",
+    "uses",
+    "with",
+    "secrets",
+];
+const STEP_KEYS: &[&str] = &[
+    "id",
+    "if",
+    "name",
+    "uses",
+    "run",
+    "shell",
+    "with",
+    "env",
+    "continue-on-error",
+    "timeout-minutes",
+    "working-directory",
+];
+const STRATEGY_KEYS: &[&str] = &["matrix", "fail-fast", "max-parallel"];
+const DEFAULTS_KEYS: &[&str] = &["run"];
+const RUN_DEFAULTS_KEYS: &[&str] = &["shell", "working-directory"];
+
+/// Events a workflow can be triggered by.
+const EVENTS: &[&str] = &[
+    "branch_protection_rule",
+    "check_run",
+    "check_suite",
+    "create",
+    "delete",
+    "deployment",
+    "deployment_status",
+    "discussion",
+    "discussion_comment",
+    "fork",
+    "gollum",
+    "issue_comment",
+    "issues",
+    "label",
+    "merge_group",
+    "milestone",
+    "page_build",
+    "project",
+    "project_card",
+    "project_column",
+    "public",
+    "pull_request",
+    "pull_request_review",
+    "pull_request_review_comment",
+    "pull_request_target",
+    "push",
+    "registry_package",
+    "release",
+    "repository_dispatch",
+    "schedule",
+    "status",
+    "watch",
+    "workflow_call",
+    "workflow_dispatch",
+    "workflow_run",
+];
+/// Filters that can't be combined with their `-ignore` counterpart.
+const EXCLUSIVE_FILTERS: &[(&str, &str)] = &[
+    ("branches", "branches-ignore"),
+    ("tags", "tags-ignore"),
+    ("paths", "paths-ignore"),
+];
+
+/// Names an expression can start with. Contexts and functions are case
+/// insensitive.
+const CONTEXTS: &[&str] = &[
+    "github", "env", "vars", "job", "jobs", "steps", "runner", "secrets", "strategy", "matrix",
+    "needs", "inputs",
+];
+const FUNCTIONS: &[&str] = &[
+    "contains",
+    "startsWith",
+    "endsWith",
+    "format",
+    "join",
+    "toJSON",
+    "fromJSON",
+    "hashFiles",
+    "success",
+    "always",
+    "cancelled",
+    "failure",
+];
+const LITERALS: &[&str] = &["true", "false", "null", "NaN", "Infinity"];
+
+/// Check a workflow file for mistakes that GitHub would reject it for or
+/// that break its runs, in the order they are written.
+pub fn lint(source: &str) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        lines: source.lines().collect(),
+        diagnostics: Vec::new(),
+    };
+    match Node::parse(source) {
+        Ok(Some(root)) if root.as_mapping().is_some() => {
+            linter.duplicate_keys(&root);
+            linter.workflow(&WorkflowFile { root });
+        }
+        Ok(Some(root)) => linter.report(root.mark, "expected a mapping of workflow keys"),
+        Ok(None) => linter.report(Mark { line: 1, col: 1 }, "the file is empty"),
+        Err(e) => linter.report(e.mark, e.message),
+    }
+
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort();
+    diagnostics.dedup();
+    diagnostics
+}
+
+struct Linter<'a> {
+    lines: Vec<&'a str>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, mark: Mark, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            mark,
+            message: message.into(),
+        });
+    }
+
+    fn workflow(&mut self, file: &WorkflowFile) {
+        let root = &file.root;
+        self.unknown_keys(root, WORKFLOW_KEYS, "the workflow");
+        if let Some(defaults) = root.get("defaults") {
+            self.defaults(defaults);
+        }
+        match root.get("on") {
+            Some(_) => self.triggers(file),
+            None => self.report(root.mark, "missing `on`: the workflow is never triggered"),
+        }
+        match root.get("jobs") {
+            Some(jobs) if jobs.as_mapping().is_some_and(|entries| !entries.is_empty()) => {
+                self.jobs(file)
+            }
+            Some(jobs) => self.report(jobs.mark, "`jobs` must map job ids to jobs"),
+            None => self.report(root.mark, "missing `jobs`"),
+        }
+        self.expressions(root);
+    }
+
+    /// Keys written twice in a mapping, of which YAML only keeps the last.
+    fn duplicate_keys(&mut self, node: &Node) {
+        match &node.value {
+            Value::Scalar { .. } => {}
+            Value::Sequence(items) => items.iter().for_each(|item| self.duplicate_keys(item)),
+            Value::Mapping(entries) => {
+                let mut seen = HashSet::new();
+                for (key, value) in entries {
+                    if let Some(name) = key.as_str()
+                        && !seen.insert(name)
+                    {
+                        self.report(key.mark, format!("duplicate key '{}'", name));
+                    }
+                    self.duplicate_keys(value);
+                }
+            }
+        }
+    }
+
+    fn unknown_keys(&mut self, node: &Node, known: &[&str], within: &str) {
+        let Some(entries) = node.as_mapping() else {
+            return;
+        };
+        for (key, _) in entries {
+            match key.as_str() {
+                Some(name) if known.contains(&name) => {}
+                Some(name) => self.report(key.mark, format!("unknown key '{}' in {}", name, within)),
+                None => self.report(key.mark, format!("keys in {} must be names", within)),
+            }
+        }
+    }
+
+    fn defaults(&mut self, defaults: &Node) {
+        self.unknown_keys(defaults, DEFAULTS_KEYS, "`defaults`");
+        if let Some(run) = defaults.get("run") {
+            self.unknown_keys(run, RUN_DEFAULTS_KEYS, "`defaults.run`");
+        }
+    }
+
+    fn triggers(&mut self, file: &WorkflowFile) {
+        let triggers = file.triggers();
+        let entries = file.root.as_mapping().unwrap_or_default();
+        if let Some((key, on)) = entries.iter().find(|(key, _)| key.as_str() == Some("on"))
+            && (on.is_null() || triggers.is_empty())
+        {
+            self.report(key.mark, "`on` must name at least one event");
+            return;
+        }
+        for (event, settings) in triggers {
+            let Some(name) = event.as_str().filter(|name| !name.is_empty()) else {
+                self.report(event.mark, "expected an event name");
+                continue;
+            };
+            if !EVENTS.contains(&name) {
+                self.report(event.mark, format!("unknown event '{}'", name));
+                continue;
+            }
+            match settings.filter(|settings| !settings.is_null()) {
+                Some(settings) => self.trigger_settings(name, settings),
+                None if name == "schedule" => {
+                    self.report(event.mark, "`schedule` needs a list of `cron` entries")
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn trigger_settings(&mut self, event: &str, settings: &Node) {
+        if event == "schedule" {
+            let valid = settings.as_sequence().is_some_and(|entries| {
+                !entries.is_empty()
+                    && entries
+                        .iter()
+                        .all(|entry| entry.get("cron").and_then(Node::as_str).is_some())
+            });
+            if !valid {
+                self.report(settings.mark, "`schedule` needs a list of `cron` entries");
+            }
+            return;
+        }
+        let Some(entries) = settings.as_mapping() else {
+            self.report(
+                settings.mark,
+                format!("settings of '{}' must be a mapping", event),
+            );
+            return;
+        };
+        for &(filter, ignore) in EXCLUSIVE_FILTERS {
+            if settings.get(filter).is_some()
+                && let Some((key, _)) = entries.iter().find(|(key, _)| key.as_str() == Some(ignore))
+            {
+                self.report(
+                    key.mark,
+                    format!("`{}` and `{}` can't both be used for '{}'", filter, ignore, event),
+                );
+            }
+        }
+    }
+
+    fn jobs(&mut self, file: &WorkflowFile) {
+        let jobs = file.jobs();
+        let ids: HashSet<&str> = jobs.iter().map(|job| job.id).collect();
+        let mut needs: HashMap<&str, Vec<&str>> = HashMap::new();
+
+        for job in &jobs {
+            if job.body.as_mapping().is_none() {
+                self.report(job.mark, format!("job '{}' must be a mapping", job.id));
+                continue;
+            }
+            self.unknown_keys(job.body, JOB_KEYS, &format!("job '{}'", job.id));
+            if job.body.get("uses").is_none() && job.body.get("runs-on").is_none() {
+                self.report(job.mark, format!("job '{}' is missing `runs-on`", job.id));
+            }
+            if let Some(defaults) = job.body.get("defaults") {
+                self.defaults(defaults);
+            }
+            if let Some(strategy) = job.body.get("strategy") {
+                self.unknown_keys(strategy, STRATEGY_KEYS, "`strategy`");
+            }
+            if let Some(steps) = job.body.get("steps") {
+                self.steps(steps);
+            }
+            self.matrix_references(job);
+
+            let dependencies = needs.entry(job.id).or_default();
+            for dependency in job_needs(job) {
+                match dependency.as_str() {
+                    Some(id) if ids.contains(id) => dependencies.push(id),
+                    Some(id) => self.report(
+                        dependency.mark,
+                        format!("job '{}' needs unknown job '{}'", job.id, id),
+                    ),
+                    None => self.report(dependency.mark, "`needs` must list job ids"),
+                }
+            }
+        }
+        self.cycles(&jobs, &needs);
+    }
+
+    fn steps(&mut self, steps: &Node) {
+        let Some(steps) = steps.as_sequence() else {
+            self.report(steps.mark, "`steps` must be a list");
+            return;
+        };
+        for step in steps {
+            if step.as_mapping().is_none() {
+                self.report(step.mark, "a step must be a mapping");
+                continue;
+            }
+            self.unknown_keys(step, STEP_KEYS, "a step");
+            match (step.get("run"), step.get("uses")) {
+                (None, None) => self.report(step.mark, "a step needs either `run` or `uses`"),
+                (Some(_), Some(uses)) => {
+                    self.report(uses.mark, "a step can't have both `run` and `uses`")
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Report each dependency cycle between jobs once, at the job written
+    /// first in it.
+    fn cycles(&mut self, jobs: &[Job], needs: &HashMap<&str, Vec<&str>>) {
+        let mut done = HashSet::new();
+        let mut reported = HashSet::new();
+        for job in jobs {
+            let mut path = Vec::new();
+            find_cycles(job.id, needs, &mut path, &mut done, &mut |cycle| {
+                let mut members = cycle.to_vec();
+                members.sort_unstable();
+                if reported.insert(members) {
+                    let first = jobs.iter().find(|job| cycle.contains(&job.id));
+                    if let Some(first) = first {
+                        let start = cycle.iter().position(|id| *id == first.id).unwrap_or(0);
+                        let mut order: Vec<&str> = cycle[start..].to_vec();
+                        order.extend_from_slice(&cycle[..start]);
+                        order.push(first.id);
+                        self.diagnostics.push(Diagnostic {
+                            mark: first.mark,
+                            message: format!("jobs depend on each other: {}", order.join(" -> ")),
+                        });
+                    }
+                }
+            });
+        }
+    }
+
+    /// `matrix.*` properties used by the job that its matrix doesn't define.
+    fn matrix_references(&mut self, job: &Job) {
+        let matrix = job.body.get("strategy").and_then(|strategy| strategy.get("matrix"));
+        // A matrix computed by an expression can define anything
+        if matrix.is_some_and(|matrix| matrix.as_mapping().is_none()) {
+            return;
+        }
+        let defined = matrix.map(matrix_keys).unwrap_or_default();
+
+        let mut scalars = Vec::new();
+        for (key, value) in job.body.as_mapping().unwrap_or_default() {
+            if key.as_str() != Some("strategy") {
+                collect_scalars(value, &mut scalars);
+            }
+        }
+        for scalar in scalars {
+            let text = scalar.as_str().unwrap_or_default();
+            for (offset, written, expression) in find_expressions(text) {
+                let Some(expression) = expression else {
+                    continue;
+                };
+                let Ok(tokens) = tokenize(expression) else {
+                    continue;
+                };
+                for property in matrix_properties(&tokens) {
+                    if defined.contains(property) {
+                        continue;
+                    }
+                    let message = match matrix {
+                        Some(_) => format!(
+                            "'matrix.{}' is not defined by the matrix of job '{}'",
+                            property, job.id
+                        ),
+                        None => format!(
+                            "'matrix.{}' is used but job '{}' has no matrix",
+                            property, job.id
+                        ),
+                    };
+                    let mark = self.locate(scalar, text, offset, written);
+                    self.report(mark, message);
+                }
+            }
+        }
+    }
+
+    /// Unclosed and malformed `${{ }}` expressions anywhere in the file.
+    fn expressions(&mut self, root: &Node) {
+        let mut scalars = Vec::new();
+        collect_scalars(root, &mut scalars);
+        for scalar in scalars {
+            let text = scalar.as_str().unwrap_or_default();
+            for (offset, written, expression) in find_expressions(text) {
+                let message = match expression {
+                    None => "unclosed `${{`: expected `}}`".to_string(),
+                    Some(expression) => match expression_error(expression) {
+                        // Expressions can span lines, but messages don't
+                        Some(error) => format!(
+                            "malformed expression `{}`: {}",
+                            written.split_whitespace().collect::<Vec<_>>().join(" "),
+                            error
+                        ),
+                        None => continue,
+                    },
+                };
+                let mark = self.locate(scalar, text, offset, written);
+                self.report(mark, message);
+            }
+        }
+    }
+
+    /// Where the part of `scalar` at `offset` of its `text` is written. The
+    /// node only knows where the scalar starts, so this looks for the same
+    /// text in the source from there, falling back to the scalar's start
+    /// when quoting or folding changed it.
+    fn locate(&self, scalar: &Node, text: &str, offset: usize, needle: &str) -> Mark {
+        let occurrence = text[..offset].matches(needle).count();
+        let span = text.lines().count() + 2;
+        let mut seen = 0;
+        for (index, line) in self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(scalar.mark.line - 1)
+            .take(span)
+        {
+            // Columns count characters, not bytes
+            let from = if index == scalar.mark.line - 1 {
+                line.char_indices()
+                    .nth(scalar.mark.col - 1)
+                    .map_or(line.len(), |(i, _)| i)
+            } else {
+                0
+            };
+            for (found, _) in line[from..].match_indices(needle) {
+                if seen == occurrence {
+                    let byte = from + found;
+                    return Mark {
+                        line: index + 1,
+                        col: line[..byte].chars().count() + 1,
+                    };
+                }
+                seen += 1;
+            }
+        }
+        scalar.mark
+    }
+}
+
+/// The jobs a job `needs`, written as one id or a list of them.
+fn job_needs<'a>(job: &Job<'a>) -> Vec<&'a Node> {
+    match job.body.get("needs") {
+        Some(needs) => match &needs.value {
+            Value::Sequence(items) => items.iter().collect(),
+            _ => vec![needs],
+        },
+        None => Vec::new(),
+    }
+}
+
+/// Depth-first search from `job`, calling `found` with the jobs of each
+/// cycle reached, in dependency order. Jobs in `done` have been searched.
+fn find_cycles<'a>(
+    job: &'a str,
+    needs: &HashMap<&'a str, Vec<&'a str>>,
+    path: &mut Vec<&'a str>,
+    done: &mut HashSet<&'a str>,
+    found: &mut impl FnMut(&[&'a str]),
+) {
+    if let Some(start) = path.iter().position(|id| *id == job) {
+        found(&path[start..]);
+        return;
+    }
+    if done.contains(job) {
+        return;
+    }
+    path.push(job);
+    for &dependency in needs.get(job).into_iter().flatten() {
+        find_cycles(dependency, needs, path, done, found);
+    }
+    path.pop();
+    done.insert(job);
+}
+
+/// The properties a matrix gives its jobs: its own keys and those of its
+/// `include` entries.
+fn matrix_keys(matrix: &Node) -> HashSet<&str> {
+    let mut keys = HashSet::new();
+    for (key, value) in matrix.as_mapping().unwrap_or_default() {
+        match key.as_str() {
+            Some("include") => {
+                for entry in value.as_sequence().unwrap_or_default() {
+                    keys.extend(
+                        entry
+                            .as_mapping()
+                            .unwrap_or_default()
+                            .iter()
+                            .filter_map(|(key, _)| key.as_str()),
+                    );
+                }
+            }
+            Some("exclude") => {}
+            Some(name) => {
+                keys.insert(name);
+            }
+            None => {}
+        }
+    }
+    keys
+}
+
+/// All scalar values under `node`; keys can't hold expressions.
+fn collect_scalars<'a>(node: &'a Node, scalars: &mut Vec<&'a Node>) {
+    match &node.value {
+        Value::Scalar { .. } => scalars.push(node),
+        Value::Sequence(items) => items.iter().for_each(|item| collect_scalars(item, scalars)),
+        Value::Mapping(entries) => entries
+            .iter()
+            .for_each(|(_, value)| collect_scalars(value, scalars)),
+    }
+}
+
+/// The `${{ }}` expressions in `text`: where each starts, how it is
+/// written, and what is between the braces. An expression that is never
+/// closed, or not before the next one opens, is written as just `${{` and
+/// has nothing between them.
+fn find_expressions(text: &str) -> Vec<(usize, &str, Option<&str>)> {
+    let mut expressions = Vec::new();
+    let mut from = 0;
+    while let Some(start) = text[from..].find("${{").map(|start| from + start) {
+        let inside = start + 3;
+        match text[inside..].find("}}") {
+            Some(end) if !text[inside..inside + end].contains("${{") => {
+                let end = inside + end + 2;
+                expressions.push((start, &text[start..end], Some(&text[inside..end - 2])));
+                from = end;
+            }
+            _ => {
+                expressions.push((start, "${{", None));
+                from = inside;
+            }
+        }
+    }
+    expressions
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    /// A context, function, property or literal name.
+    Name(&'a str),
+    /// A string or number.
+    Literal,
+    Operator,
+    Punctuation(char),
+}
+
+/// Split an expression into tokens, or say why it can't be.
+fn tokenize(expression: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '\'' => {
+                // Quotes inside strings are doubled
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) if chars.peek().is_some_and(|&(_, c)| c == '\'') => {
+                            chars.next();
+                        }
+                        Some((_, '\'')) => break,
+                        Some(_) => {}
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                tokens.push(Token::Literal);
+            }
+            c if c.is_ascii_digit() => {
+                while chars
+                    .next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '.')
+                    .is_some()
+                {}
+                tokens.push(Token::Literal);
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_' || c == '-')
+                {
+                    end = i + c.len_utf8();
+                }
+                tokens.push(Token::Name(&expression[start..end]));
+            }
+            '=' | '!' | '<' | '>' => {
+                let equals = chars.next_if(|&(_, c)| c == '=').is_some();
+                if c == '=' && !equals {
+                    return Err("`=` is not an operator; compare with `==`".to_string());
+                }
+                tokens.push(if c == '!' && !equals {
+                    Token::Punctuation('!')
+                } else {
+                    Token::Operator
+                });
+            }
+            '&' | '|' => {
+                if chars.next_if(|&(_, next)| next == c).is_none() {
+                    return Err(format!("`{}` is not an operator; use `{}{}`", c, c, c));
+                }
+                tokens.push(Token::Operator);
+            }
+            '.' | '(' | ')' | '[' | ']' | ',' | '*' | '-' => tokens.push(Token::Punctuation(c)),
+            c => return Err(format!("unexpected character '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Why an expression (the text between `${{` and `}}`) is malformed, if
+/// it is.
+fn expression_error(expression: &str) -> Option<String> {
+    let tokens = match tokenize(expression) {
+        Ok(tokens) => tokens,
+        Err(error) => return Some(error),
+    };
+    if tokens.is_empty() {
+        return Some("the expression is empty".to_string());
+    }
+
+    let mut open = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        match *token {
+            Token::Punctuation(c @ ('(' | '[')) => open.push(c),
+            Token::Punctuation(c @ (')' | ']')) => {
+                let expected = if c == ')' { '(' } else { '[' };
+                if open.pop() != Some(expected) {
+                    return Some(format!("unbalanced '{}'", c));
+                }
+            }
+            Token::Name(name) => {
+                // Properties can be called anything
+                if i > 0 && tokens[i - 1] == Token::Punctuation('.') {
+                    continue;
+                }
+                let is_call = tokens.get(i + 1) == Some(&Token::Punctuation('('));
+                let known = |names: &[&str]| names.iter().any(|n| n.eq_ignore_ascii_case(name));
+                if is_call && !known(FUNCTIONS) {
+                    return Some(format!("unknown function '{}'", name));
+                }
+                if !is_call && !known(CONTEXTS) && !LITERALS.contains(&name) {
+                    return Some(format!("unknown context '{}'", name));
+                }
+            }
+            _ => {}
+        }
+    }
+    open.last().map(|c| format!("unclosed '{}'", c))
+}
+
+/// Properties read from the `matrix` context with `matrix.name`.
+fn matrix_properties<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+    tokens
+        .windows(3)
+        .enumerate()
+        .filter_map(|(i, window)| match *window {
+            [Token::Name(context), Token::Punctuation('.'), Token::Name(property)]
+                if context.eq_ignore_ascii_case("matrix")
+                    && (i == 0 || tokens[i - 1] != Token::Punctuation('.')) =>
+            {
+                Some(property)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The diagnostics of `source` as `line:col: message`.
+    fn check(source: &str) -> Vec<String> {
+        lint(source)
+            .into_iter()
+            .map(|d| format!("{}:{}: {}", d.mark.line, d.mark.col, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn valid_workflow_has_no_diagnostics() {
+        let source = "\
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ github.sha }}
+";
+        assert_eq!(check(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let source = "\
+on: push
+jobs:
+  build:
+    runs-on: a
+    runs-on: b
+    steps: [{run: x}]
+";
+        assert_eq!(check(source), ["5:5: duplicate key 'runs-on'"]);
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let source = "\
+on: push
+colour: red
+jobs:
+  build:
+    runs-on: x
+    step: []
+";
+        assert_eq!(
+            check(source),
+            [
+                "2:1: unknown key 'colour' in the workflow",
+                "6:5: unknown key 'step' in job 'build'",
+            ]
+        );
+    }
+
+    #[test]
+    fn branches_with_branches_ignore() {
+        let source = "\
+on:
+  push:
+    branches: [main]
+    branches-ignore: [dev]
+jobs:
+  build:
+    runs-on: x
+";
+        assert_eq!(
+            check(source),
+            ["4:5: `branches` and `branches-ignore` can't both be used for 'push'"]
+        );
+    }
+
+    #[test]
+    fn needs_cycle_is_reported_once() {
+        let source = "\
+on: push
+jobs:
+  a:
+    runs-on: x
+    needs: c
+  b:
+    runs-on: x
+    needs: a
+  c:
+    runs-on: x
+    needs: [b, d]
+";
+        assert_eq!(
+            check(source),
+            [
+                "3:3: jobs depend on each other: a -> c -> b -> a",
+                "11:16: job 'c' needs unknown job 'd'",
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_expression() {
+        let source = "\
+on: push
+jobs:
+  build:
+    runs-on: x
+    steps:
+      - run: echo ${{ github.sha } and ${{ github.ref }}
+";
+        assert_eq!(check(source), ["6:19: unclosed `${{`: expected `}}`"]);
+    }
+
+    #[test]
+    fn single_equals_is_not_an_operator() {
+        let source = "\
+on: push
+jobs:
+  build:
+    runs-on: x
+    if: ${{ github.ref = 'main' }}
+  test:
+    runs-on: x
+    if: ${{ github.ref == 'main' }}
+";
+        assert_eq!(
+            check(source),
+            [
+                "5:9: malformed expression `${{ github.ref = 'main' }}`: \
+                 `=` is not an operator; compare with `==`"
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_count_characters_on_non_ascii_lines() {
+        // `é` and `—` take two and three bytes, but one column each
+        let source = "\
+on: push
+jobs:
+  build:
+    runs-on: x
+    steps:
+      - name: café — ${{ nope }}
+        run: ok
+";
+        assert_eq!(
+            check(source),
+            ["6:22: malformed expression `${{ nope }}`: unknown context 'nope'"]
+        );
+    }
+
+    #[test]
+    fn undefined_matrix_key() {
+        let source = "\
+on: push
+jobs:
+  build:
+    runs-on: ${{ matrix.os }}
+    strategy:
+      matrix:
+        os: [a]
+        include:
+          - node: 18
+    steps:
+      - run: ${{ matrix.node }} ${{ matrix.arch }}
+";
+        assert_eq!(
+            check(source),
+            ["11:33: 'matrix.arch' is not defined by the matrix of job 'build'"]
+        );
+    }
+
+    #[test]
+    fn missing_runs_on_and_parse_errors() {
+        assert_eq!(
+            check("on: push\njobs:\n  build:\n    steps: [{run: x}]\n"),
+            ["3:3: job 'build' is missing `runs-on`"]
+        );
+        assert_eq!(check("on: push\njobs: [\n").len(), 1);
+    }
+}
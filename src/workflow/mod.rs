@@ -0,0 +1,7 @@
+pub mod inputs;
+pub mod lint;
+pub mod model;
+pub mod yaml;
+
+pub use inputs::{DispatchInput, InputKind, dispatch_inputs};
+pub use model::WorkflowFile;
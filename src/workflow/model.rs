@@ -0,0 +1,93 @@
+use crate::utils::error::AppError;
+use super::yaml::{Mark, Node, Value};
+
+/// A workflow file from `.github/workflows`, keeping where each part of it
+/// is written.
+#[derive(Debug, Clone)]
+pub struct WorkflowFile {
+    pub root: Node,
+}
+
+impl WorkflowFile {
+    pub fn parse(source: &str) -> Result<Self, AppError> {
+        match Node::parse(source)? {
+            Some(root) if root.as_mapping().is_some() => Ok(Self { root }),
+            Some(root) => Err(AppError::InvalidWorkflow(format!(
+                "{}:{}: expected a mapping of workflow keys",
+                root.mark.line, root.mark.col
+            ))),
+            None => Err(AppError::InvalidWorkflow("the file is empty".to_string())),
+        }
+    }
+
+    /// The events under `on`, with their settings when `on` maps them to
+    /// some rather than naming or listing them.
+    pub fn triggers(&self) -> Vec<(&Node, Option<&Node>)> {
+        let Some(on) = self.root.get("on") else {
+            return Vec::new();
+        };
+        match &on.value {
+            Value::Scalar { .. } => vec![(on, None)],
+            Value::Sequence(events) => events.iter().map(|event| (event, None)).collect(),
+            Value::Mapping(entries) => entries
+                .iter()
+                .map(|(event, settings)| (event, Some(settings)))
+                .collect(),
+        }
+    }
+
+    /// The jobs under `jobs`, in the order they are defined.
+    pub fn jobs(&self) -> Vec<Job<'_>> {
+        let Some(entries) = self.root.get("jobs").and_then(Node::as_mapping) else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .filter_map(|(key, body)| {
+                Some(Job {
+                    id: key.as_str()?,
+                    mark: key.mark,
+                    body,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A job defined in a workflow file.
+#[derive(Debug, Clone, Copy)]
+pub struct Job<'a> {
+    pub id: &'a str,
+    /// Where the job's id is written.
+    pub mark: Mark,
+    pub body: &'a Node,
+}
+
+impl Job<'_> {
+    /// The name GitHub shows for the job: its `name`, or else its id.
+    pub fn name(&self) -> &str {
+        self.body
+            .get("name")
+            .and_then(Node::as_str)
+            .unwrap_or(self.id)
+    }
+
+    /// Whether `run_job`, the name of a job of a run as the API reports it,
+    /// was run from this definition.
+    pub fn runs_as(&self, run_job: &str) -> bool {
+        let name = self.name();
+        // Jobs calling a reusable workflow report as `caller / called job`,
+        // matrix jobs with their values appended as `build (ubuntu, 18)`
+        let caller = run_job.split(" / ").next().unwrap_or(run_job);
+        let base = caller.split(" (").next().unwrap_or(caller);
+        match name.split_once("${{") {
+            // Only the literal start of a computed name can be compared
+            Some((prefix, _)) => {
+                let prefix = prefix.trim_end();
+                !prefix.is_empty() && run_job.starts_with(prefix)
+            }
+            None => [run_job, caller, base].contains(&name),
+        }
+    }
+}
+
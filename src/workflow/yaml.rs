@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+
+use crate::utils::error::AppError;
+
+/// A position in a YAML source, both 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mark {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl From<Marker> for Mark {
+    fn from(marker: Marker) -> Self {
+        Self {
+            line: marker.line(),
+            col: marker.col() + 1,
+        }
+    }
+}
+
+/// Why a YAML source couldn't be parsed, and where.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub mark: Mark,
+    pub message: String,
+}
+
+impl From<ParseError> for AppError {
+    fn from(error: ParseError) -> Self {
+        AppError::InvalidWorkflow(format!(
+            "{}:{}: {}",
+            error.mark.line, error.mark.col, error.message
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// A scalar as written. Quoted scalars are always strings, never null,
+    /// booleans or numbers.
+    Scalar { text: String, quoted: bool },
+    Sequence(Vec<Node>),
+    /// Entries in the order they are written, duplicates included.
+    Mapping(Vec<(Node, Node)>),
+}
+
+/// A YAML node with the position it starts at, which `yaml_rust2::Yaml`
+/// doesn't keep. Aliases are resolved to a copy of their anchor.
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub value: Value,
+    pub mark: Mark,
+}
+
+impl Node {
+    /// Parse the first document of `source`; `None` when it is empty.
+    pub fn parse(source: &str) -> Result<Option<Node>, ParseError> {
+        let mut builder = Builder::default();
+        Parser::new_from_str(source)
+            .load(&mut builder, false)
+            .map_err(|e| ParseError {
+                mark: Mark::from(*e.marker()),
+                message: e.info().to_string(),
+            })?;
+        Ok(builder.root)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            Value::Scalar { text, .. } => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_sequence(&self) -> Option<&[Node]> {
+        match &self.value {
+            Value::Sequence(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_mapping(&self) -> Option<&[(Node, Node)]> {
+        match &self.value {
+            Value::Mapping(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// The value of `key` in a mapping.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        self.as_mapping()?
+            .iter()
+            .find(|(k, _)| k.as_str() == Some(key))
+            .map(|(_, value)| value)
+    }
+
+    /// The value of an unquoted `true` or `false`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match &self.value {
+            Value::Scalar {
+                text,
+                quoted: false,
+            } => match text.as_str() {
+                "true" | "True" | "TRUE" => Some(true),
+                "false" | "False" | "FALSE" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether the node is an empty value, `~` or `null`.
+    pub fn is_null(&self) -> bool {
+        matches!(
+            &self.value,
+            Value::Scalar { text, quoted: false } if matches!(text.as_str(), "" | "~" | "null" | "Null" | "NULL")
+        )
+    }
+}
+
+/// A mapping or sequence being read. Mappings hold a key until its value
+/// arrives.
+enum Collection {
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>, Option<Node>),
+}
+
+/// Builds the node tree from the parser's events.
+#[derive(Default)]
+struct Builder {
+    /// Open collections with where they start and their anchor.
+    stack: Vec<(Collection, Mark, usize)>,
+    anchors: HashMap<usize, Node>,
+    root: Option<Node>,
+}
+
+impl Builder {
+    fn insert(&mut self, node: Node, anchor: usize) {
+        // Anchor ids start at 1
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+        match self.stack.last_mut() {
+            Some((Collection::Sequence(items), ..)) => items.push(node),
+            Some((Collection::Mapping(entries, key), ..)) => match key.take() {
+                Some(key) => entries.push((key, node)),
+                None => *key = Some(node),
+            },
+            // Only the first document is kept
+            None if self.root.is_none() => self.root = Some(node),
+            None => {}
+        }
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        let mark = Mark::from(marker);
+        match event {
+            Event::Scalar(text, style, anchor, _) => {
+                let quoted = style != TScalarStyle::Plain;
+                self.insert(
+                    Node {
+                        value: Value::Scalar { text, quoted },
+                        mark,
+                    },
+                    anchor,
+                );
+            }
+            Event::Alias(anchor) => {
+                // An undefined alias is a parse error, so this always finds one
+                if let Some(node) = self.anchors.get(&anchor).cloned() {
+                    self.insert(Node { mark, ..node }, 0);
+                }
+            }
+            Event::SequenceStart(anchor, _) => {
+                self.stack.push((Collection::Sequence(Vec::new()), mark, anchor));
+            }
+            Event::MappingStart(anchor, _) => {
+                self.stack
+                    .push((Collection::Mapping(Vec::new(), None), mark, anchor));
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                let Some((collection, mark, anchor)) = self.stack.pop() else {
+                    return;
+                };
+                // Block collections are only known to start once their first
+                // entry has been read, which is where they begin
+                let (value, first) = match collection {
+                    Collection::Sequence(items) => {
+                        let first = items.first().map(|item| item.mark);
+                        (Value::Sequence(items), first)
+                    }
+                    Collection::Mapping(entries, _) => {
+                        let first = entries.first().map(|(key, _)| key.mark);
+                        (Value::Mapping(entries), first)
+                    }
+                };
+                let mark = first.map_or(mark, |first| first.min(mark));
+                self.insert(Node { value, mark }, anchor);
+            }
+            _ => {}
+        }
+    }
+}